@@ -63,9 +63,9 @@ fn bm_coder<C: Coder>(name: &str, max_bytes: &[usize], c: &mut Criterion) {
         let mut tags = vec![0u8; tbytes];
         let mut data = vec![0u8; dbytes];
         let data_len = if delta {
-            coder.encode_deltas(C::Elem::one(), &values, &mut tags, &mut data)
+            coder.encode_deltas(C::Elem::one(), values, &mut tags, &mut data)
         } else {
-            coder.encode(&values, &mut tags, &mut data)
+            coder.encode(values, &mut tags, &mut data)
         };
         data.resize(data_len, 0);
         data.shrink_to_fit();
@@ -89,7 +89,7 @@ fn bm_coder<C: Coder>(name: &str, max_bytes: &[usize], c: &mut Criterion) {
                 let (tbytes, dbytes) = C::max_compressed_bytes(v.len());
                 let mut tags = vec![0u8; tbytes];
                 let mut data = vec![0u8; dbytes];
-                b.iter(|| assert!(coder.encode(&v, &mut tags, &mut data) <= max_data_len))
+                b.iter(|| assert!(coder.encode(v, &mut tags, &mut data) <= max_data_len))
             },
         );
 
@@ -104,7 +104,7 @@ fn bm_coder<C: Coder>(name: &str, max_bytes: &[usize], c: &mut Criterion) {
                 let mut data = vec![0u8; dbytes];
                 b.iter(|| {
                     assert!(
-                        coder.encode_deltas(C::Elem::one(), &v, &mut tags, &mut data)
+                        coder.encode_deltas(C::Elem::one(), v, &mut tags, &mut data)
                             <= max_data_len
                     )
                 })
@@ -0,0 +1,296 @@
+#ifndef STREAMVBYTE64_H
+#define STREAMVBYTE64_H
+
+/* Generated by cbindgen from src/capi.rs. Do not edit by hand. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Status codes returned by all C functions.
+typedef enum Streamvbyte64Status {
+  // The call succeeded.
+  STREAMVBYTE64_STATUS_OK = 0,
+  // A required pointer argument was null.
+  STREAMVBYTE64_STATUS_NULL_POINTER = -1,
+  // The number of values is not a multiple of 4.
+  STREAMVBYTE64_STATUS_INVALID_LENGTH = -2,
+  // An output buffer is too small to hold the result.
+  STREAMVBYTE64_STATUS_BUFFER_TOO_SMALL = -3,
+  // The data stream ends before all of the groups described by the tags.
+  STREAMVBYTE64_STATUS_TRUNCATED = -4,
+  // A length is too large to be represented.
+  STREAMVBYTE64_STATUS_OVERFLOW = -5,
+  // An unexpected internal error occurred.
+  STREAMVBYTE64_STATUS_PANIC = -6,
+} Streamvbyte64Status;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Writes the number of tag and data bytes required to encode `len` values.
+//
+// # Safety
+//
+// `tags_len` and `data_len` must be valid for writes.
+enum Streamvbyte64Status streamvbyte64_coder0124_max_compressed_bytes(size_t len,
+                                                                      size_t *tags_len,
+                                                                      size_t *data_len);
+
+// Encodes `len` values, writing the number of data bytes used to `written`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder0124_encode(const uint32_t *values,
+                                                        size_t len,
+                                                        uint8_t *tags,
+                                                        size_t tags_len,
+                                                        uint8_t *data,
+                                                        size_t data_len,
+                                                        size_t *written);
+
+// Encodes `len` values as deltas from `initial`, writing the number of data bytes used
+// to `written`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder0124_encode_deltas(uint32_t initial,
+                                                               const uint32_t *values,
+                                                               size_t len,
+                                                               uint8_t *tags,
+                                                               size_t tags_len,
+                                                               uint8_t *data,
+                                                               size_t data_len,
+                                                               size_t *written);
+
+// Decodes `len` values, writing the number of data bytes consumed to `read`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder0124_decode(const uint8_t *tags,
+                                                        size_t tags_len,
+                                                        const uint8_t *data,
+                                                        size_t data_len,
+                                                        uint32_t *values,
+                                                        size_t len,
+                                                        size_t *read);
+
+// Decodes `len` values as deltas from `initial`, writing the number of data bytes
+// consumed to `read`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder0124_decode_deltas(uint32_t initial,
+                                                               const uint8_t *tags,
+                                                               size_t tags_len,
+                                                               const uint8_t *data,
+                                                               size_t data_len,
+                                                               uint32_t *values,
+                                                               size_t len,
+                                                               size_t *read);
+
+// Writes the data length of all the groups encoded by `tags` to `len`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder0124_data_len(const uint8_t *tags,
+                                                          size_t tags_len,
+                                                          size_t *len);
+
+// Skips `tags_len * 4` deltas, writing the number of data bytes consumed to `read` and
+// the sum of the skipped deltas to `sum`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder0124_skip_deltas(const uint8_t *tags,
+                                                             size_t tags_len,
+                                                             const uint8_t *data,
+                                                             size_t data_len,
+                                                             size_t *read,
+                                                             uint32_t *sum);
+
+// Writes the number of tag and data bytes required to encode `len` values.
+//
+// # Safety
+//
+// `tags_len` and `data_len` must be valid for writes.
+enum Streamvbyte64Status streamvbyte64_coder1234_max_compressed_bytes(size_t len,
+                                                                      size_t *tags_len,
+                                                                      size_t *data_len);
+
+// Encodes `len` values, writing the number of data bytes used to `written`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder1234_encode(const uint32_t *values,
+                                                        size_t len,
+                                                        uint8_t *tags,
+                                                        size_t tags_len,
+                                                        uint8_t *data,
+                                                        size_t data_len,
+                                                        size_t *written);
+
+// Encodes `len` values as deltas from `initial`, writing the number of data bytes used
+// to `written`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder1234_encode_deltas(uint32_t initial,
+                                                               const uint32_t *values,
+                                                               size_t len,
+                                                               uint8_t *tags,
+                                                               size_t tags_len,
+                                                               uint8_t *data,
+                                                               size_t data_len,
+                                                               size_t *written);
+
+// Decodes `len` values, writing the number of data bytes consumed to `read`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder1234_decode(const uint8_t *tags,
+                                                        size_t tags_len,
+                                                        const uint8_t *data,
+                                                        size_t data_len,
+                                                        uint32_t *values,
+                                                        size_t len,
+                                                        size_t *read);
+
+// Decodes `len` values as deltas from `initial`, writing the number of data bytes
+// consumed to `read`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder1234_decode_deltas(uint32_t initial,
+                                                               const uint8_t *tags,
+                                                               size_t tags_len,
+                                                               const uint8_t *data,
+                                                               size_t data_len,
+                                                               uint32_t *values,
+                                                               size_t len,
+                                                               size_t *read);
+
+// Writes the data length of all the groups encoded by `tags` to `len`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder1234_data_len(const uint8_t *tags,
+                                                          size_t tags_len,
+                                                          size_t *len);
+
+// Skips `tags_len * 4` deltas, writing the number of data bytes consumed to `read` and
+// the sum of the skipped deltas to `sum`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder1234_skip_deltas(const uint8_t *tags,
+                                                             size_t tags_len,
+                                                             const uint8_t *data,
+                                                             size_t data_len,
+                                                             size_t *read,
+                                                             uint32_t *sum);
+
+// Writes the number of tag and data bytes required to encode `len` values.
+//
+// # Safety
+//
+// `tags_len` and `data_len` must be valid for writes.
+enum Streamvbyte64Status streamvbyte64_coder1248_max_compressed_bytes(size_t len,
+                                                                      size_t *tags_len,
+                                                                      size_t *data_len);
+
+// Encodes `len` values, writing the number of data bytes used to `written`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder1248_encode(const uint64_t *values,
+                                                        size_t len,
+                                                        uint8_t *tags,
+                                                        size_t tags_len,
+                                                        uint8_t *data,
+                                                        size_t data_len,
+                                                        size_t *written);
+
+// Encodes `len` values as deltas from `initial`, writing the number of data bytes used
+// to `written`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder1248_encode_deltas(uint64_t initial,
+                                                               const uint64_t *values,
+                                                               size_t len,
+                                                               uint8_t *tags,
+                                                               size_t tags_len,
+                                                               uint8_t *data,
+                                                               size_t data_len,
+                                                               size_t *written);
+
+// Decodes `len` values, writing the number of data bytes consumed to `read`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder1248_decode(const uint8_t *tags,
+                                                        size_t tags_len,
+                                                        const uint8_t *data,
+                                                        size_t data_len,
+                                                        uint64_t *values,
+                                                        size_t len,
+                                                        size_t *read);
+
+// Decodes `len` values as deltas from `initial`, writing the number of data bytes
+// consumed to `read`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder1248_decode_deltas(uint64_t initial,
+                                                               const uint8_t *tags,
+                                                               size_t tags_len,
+                                                               const uint8_t *data,
+                                                               size_t data_len,
+                                                               uint64_t *values,
+                                                               size_t len,
+                                                               size_t *read);
+
+// Writes the data length of all the groups encoded by `tags` to `len`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder1248_data_len(const uint8_t *tags,
+                                                          size_t tags_len,
+                                                          size_t *len);
+
+// Skips `tags_len * 4` deltas, writing the number of data bytes consumed to `read` and
+// the sum of the skipped deltas to `sum`.
+//
+// # Safety
+//
+// All pointers must be valid for the lengths provided alongside them.
+enum Streamvbyte64Status streamvbyte64_coder1248_skip_deltas(const uint8_t *tags,
+                                                             size_t tags_len,
+                                                             const uint8_t *data,
+                                                             size_t data_len,
+                                                             size_t *read,
+                                                             uint64_t *sum);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* STREAMVBYTE64_H */
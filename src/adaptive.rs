@@ -0,0 +1,592 @@
+//! Stream of 32-bit values that selects a coder and delta mode independently for each block.
+
+use crate::block::pad_group;
+use crate::{AutoCoder, Coder, Coder0124, Coder1234, CoderId, Error};
+use std::ops::Range;
+
+/// Version of the serialized stream format written by this library.
+const FORMAT_VERSION: u8 = 1;
+/// Length of the serialized stream header in bytes.
+const HEADER_LEN: usize = 16;
+/// Length of the header preceding each block in bytes.
+const BLOCK_HEADER_LEN: usize = 4;
+/// Block header flag set when values are delta coded.
+const FLAG_DELTA: u8 = 0x1;
+/// Default number of values in each block.
+const DEFAULT_BLOCK_LEN: usize = 256;
+
+/// An encoded stream of `u32` values where each block of values chooses its own coding.
+///
+/// Columns often change character partway through: runs of zeros, then dense small values, then
+/// large outliers. Each block of `block_len()` values is encoded with whichever of `Coder0124` and
+/// `Coder1234`, with or without delta coding, produces the smallest output, and a 4 byte header
+/// records the choice and the data length of the block. Delta coded blocks start from zero so
+/// that every block may be decoded without the blocks that precede it.
+///
+/// The offset of each block is recorded when the stream is encoded or parsed, so `block()`,
+/// `get()` and `decode_range()` seek to a block in constant time. Within a block only the groups
+/// that hold the requested values are decoded.
+///
+/// ```
+/// use streamvbyte64::{AdaptiveStream, CoderId};
+///
+/// let mut values = vec![0u32; 256];
+/// values.extend((0..256u32).map(|v| 1_000_000 + v * 7));
+/// values.extend((0..256u32).map(|v| v.wrapping_mul(2_654_435_761)));
+///
+/// let stream = AdaptiveStream::encode(&values);
+/// assert_eq!(3, stream.num_blocks());
+/// assert_eq!(CoderId::Coder0124, stream.block(0).coder_id());
+/// assert!(stream.block(1).is_delta());
+/// assert!(!stream.block(2).is_delta());
+///
+/// let parsed = AdaptiveStream::from_bytes(stream.as_bytes()).unwrap();
+/// assert_eq!(values, parsed.decode());
+/// assert_eq!(Some(1_000_007), parsed.get(257));
+///
+/// let mut range = vec![];
+/// parsed.decode_range(250..260, &mut range);
+/// assert_eq!(&values[250..260], &range[..]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdaptiveStream {
+    block_len: usize,
+    len: usize,
+    bytes: Vec<u8>,
+    /// Offset of the header of each block in `bytes`.
+    offsets: Vec<usize>,
+}
+
+impl AdaptiveStream {
+    /// Encode `values` in blocks of 256 values.
+    pub fn encode(values: &[u32]) -> Self {
+        Self::encode_with_block_len(values, DEFAULT_BLOCK_LEN)
+    }
+
+    /// Encode `values` in blocks of `block_len` values.
+    ///
+    /// # Panics
+    ///
+    /// - If `block_len` is zero or not a multiple of 4.
+    /// - If `block_len > 16380`, as the data length of each block must fit in 16 bits.
+    pub fn encode_with_block_len(values: &[u32], block_len: usize) -> Self {
+        assert!(block_len > 0 && block_len.is_multiple_of(4));
+        assert!(block_len * 4 <= u16::MAX as usize);
+        let mut bytes = Vec::with_capacity(HEADER_LEN + values.len() * 2);
+        bytes.push(FORMAT_VERSION);
+        bytes.extend_from_slice(&[0u8; 3]);
+        bytes.extend_from_slice(&(block_len as u32).to_le_bytes());
+        bytes.extend_from_slice(&(values.len() as u64).to_le_bytes());
+
+        let mut offsets = Vec::with_capacity(values.len().div_ceil(block_len));
+        let mut buf = Vec::with_capacity(block_len);
+        for chunk in values.chunks(block_len) {
+            offsets.push(bytes.len());
+            encode_block(chunk, &mut buf, &mut bytes);
+        }
+        Self {
+            block_len,
+            len: values.len(),
+            bytes,
+            offsets,
+        }
+    }
+
+    /// Parse and copy a stream serialized by `as_bytes()`.
+    ///
+    /// The header of every block is validated, including that the data length recorded in the
+    /// block header matches the length described by its tags.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < HEADER_LEN {
+            return Err(Error::Truncated);
+        }
+        if bytes[0] != FORMAT_VERSION {
+            return Err(Error::UnsupportedVersion(bytes[0]));
+        }
+        if bytes[1..4] != [0u8; 3] {
+            return Err(Error::InvalidHeader("reserved"));
+        }
+        let block_len = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        if block_len == 0 || !block_len.is_multiple_of(4) || block_len * 4 > u16::MAX as usize {
+            return Err(Error::InvalidHeader("block_len"));
+        }
+        let len = usize::try_from(u64::from_le_bytes(bytes[8..16].try_into().unwrap()))
+            .map_err(|_| Error::InvalidHeader("len"))?;
+
+        let mut offsets = vec![];
+        let mut offset = HEADER_LEN;
+        for start in (0..len).step_by(block_len) {
+            offsets.push(offset);
+            let block = parse_block(&bytes[offset..], std::cmp::min(block_len, len - start))?;
+            block.validate()?;
+            offset += BLOCK_HEADER_LEN + block.tags.len() + block.data.len();
+        }
+        if offset != bytes.len() {
+            return Err(Error::InvalidHeader("trailing bytes"));
+        }
+        Ok(Self {
+            block_len,
+            len,
+            bytes: bytes.to_vec(),
+            offsets,
+        })
+    }
+
+    /// Returns the serialized stream.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the number of values in the stream.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the stream contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the total length of the data streams of all blocks, excluding the stream header,
+    /// block headers and tag streams.
+    pub fn data_len(&self) -> usize {
+        // Every block but the last holds a multiple of 4 values, so the tags of all blocks
+        // describe `len` values padded to a whole group.
+        self.bytes.len() - HEADER_LEN - self.offsets.len() * BLOCK_HEADER_LEN - self.len.div_ceil(4)
+    }
+
+    /// Returns the number of values in each block. The last block may be shorter.
+    pub fn block_len(&self) -> usize {
+        self.block_len
+    }
+
+    /// Returns the number of blocks in the stream.
+    pub fn num_blocks(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Returns the block at index `block`.
+    ///
+    /// # Panics
+    ///
+    /// - If `block >= num_blocks()`.
+    pub fn block(&self, block: usize) -> AdaptiveBlock<'_> {
+        let len = std::cmp::min(self.block_len, self.len - block * self.block_len);
+        // Blocks were validated when the stream was encoded or parsed.
+        parse_block(&self.bytes[self.offsets[block]..], len).unwrap()
+    }
+
+    /// Iterate over all blocks in the stream.
+    pub fn blocks(&self) -> impl ExactSizeIterator<Item = AdaptiveBlock<'_>> + '_ {
+        (0..self.num_blocks()).map(|b| self.block(b))
+    }
+
+    /// Returns the value at `index`, or `None` if `index >= len()`.
+    ///
+    /// Only the value at `index` is decoded; see `AdaptiveBlock::get()`.
+    pub fn get(&self, index: usize) -> Option<u32> {
+        if index >= self.len {
+            return None;
+        }
+        self.block(index / self.block_len)
+            .get(index % self.block_len)
+    }
+
+    /// Decode the values at positions `range` and append them to `values`.
+    ///
+    /// Blocks before the range are passed over using the recorded block offsets, and only the
+    /// groups that overlap `range` are decoded within each block.
+    ///
+    /// # Panics
+    ///
+    /// - If `range.start > range.end` or `range.end > len()`.
+    pub fn decode_range(&self, range: Range<usize>, values: &mut Vec<u32>) {
+        assert!(range.start <= range.end && range.end <= self.len);
+        values.reserve(range.len());
+        let mut start = range.start;
+        while start < range.end {
+            let block_start = start - start % self.block_len;
+            let end = std::cmp::min(range.end, block_start + self.block_len);
+            self.block(block_start / self.block_len)
+                .decode_range_into((start - block_start)..(end - block_start), values);
+            start = end;
+        }
+    }
+
+    /// Decode all values in the stream.
+    pub fn decode(&self) -> Vec<u32> {
+        let mut values = Vec::with_capacity(self.len);
+        self.decode_into(&mut values);
+        values
+    }
+
+    /// Decode all values in the stream and append them to `values`.
+    pub fn decode_into(&self, values: &mut Vec<u32>) {
+        values.reserve(self.len);
+        for block in self.blocks() {
+            block.decode_into(values);
+        }
+    }
+}
+
+/// A single block of an `AdaptiveStream`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AdaptiveBlock<'a> {
+    coder: CoderId,
+    delta: bool,
+    len: usize,
+    tags: &'a [u8],
+    data: &'a [u8],
+}
+
+impl<'a> AdaptiveBlock<'a> {
+    /// Returns the coder used to encode this block.
+    pub fn coder_id(&self) -> CoderId {
+        self.coder
+    }
+
+    /// Returns true if values in this block were delta coded from zero.
+    pub fn is_delta(&self) -> bool {
+        self.delta
+    }
+
+    /// Returns the number of values in this block.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the block contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the tag stream.
+    pub fn tags(&self) -> &'a [u8] {
+        self.tags
+    }
+
+    /// Returns the data stream.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Decode all values in this block and append them to `values`.
+    pub fn decode_into(&self, values: &mut Vec<u32>) {
+        let start = values.len();
+        match self.coder {
+            CoderId::Coder0124 => self.decode_with(Coder0124::new(), values),
+            CoderId::Coder1234 => self.decode_with(Coder1234::new(), values),
+            CoderId::Coder1248 => unreachable!("u64 coders are rejected when parsing"),
+        }
+        values.truncate(start + self.len);
+    }
+
+    /// Returns the value at `index` within this block, or `None` if `index >= len()`.
+    ///
+    /// The data offset of the group containing `index` is computed from the tags and only the
+    /// requested value is decoded with `Coder::decode_value()`. In delta coded blocks the deltas
+    /// of preceding groups are summed with `Coder::skip_deltas()`.
+    pub fn get(&self, index: usize) -> Option<u32> {
+        if index >= self.len {
+            return None;
+        }
+        Some(match self.coder {
+            CoderId::Coder0124 => self.get_with(Coder0124::new(), index),
+            CoderId::Coder1234 => self.get_with(Coder1234::new(), index),
+            CoderId::Coder1248 => unreachable!("u64 coders are rejected when parsing"),
+        })
+    }
+
+    /// Decode the values at positions `range` within this block and append them to `values`.
+    ///
+    /// # Panics
+    ///
+    /// - If `range.start > range.end` or `range.end > len()`.
+    pub fn decode_range_into(&self, range: Range<usize>, values: &mut Vec<u32>) {
+        assert!(range.start <= range.end && range.end <= self.len);
+        let start = values.len();
+        values.resize(start + range.len(), 0);
+        let out = &mut values[start..];
+        match self.coder {
+            CoderId::Coder0124 => self.decode_range_with(Coder0124::new(), range, out),
+            CoderId::Coder1234 => self.decode_range_with(Coder1234::new(), range, out),
+            CoderId::Coder1248 => unreachable!("u64 coders are rejected when parsing"),
+        }
+    }
+
+    /// Decode the value at `index` with `coder`.
+    fn get_with<C: Coder<Elem = u32>>(&self, coder: C, index: usize) -> u32 {
+        let group = index / 4;
+        let tag = self.tags[group];
+        if self.delta {
+            let (offset, base) = coder.skip_deltas(&self.tags[..group], self.data);
+            let data = &self.data[offset..];
+            (0..=(index % 4)).fold(base, |v, i| {
+                v.wrapping_add(coder.decode_value(tag, data, i))
+            })
+        } else {
+            let offset = coder.data_len(&self.tags[..group]);
+            coder.decode_value(tag, &self.data[offset..], index % 4)
+        }
+    }
+
+    /// Decode the values at positions `range` with `coder` into `values`.
+    fn decode_range_with<C: Coder<Elem = u32>>(
+        &self,
+        coder: C,
+        range: Range<usize>,
+        values: &mut [u32],
+    ) {
+        if self.delta {
+            coder.decode_deltas_range(0, self.tags, self.data, range, values);
+        } else {
+            coder.decode_range(self.tags, self.data, range, values);
+        }
+    }
+
+    /// Decode all groups in this block, including padding, with `coder`.
+    fn decode_with<C: Coder<Elem = u32>>(&self, coder: C, values: &mut Vec<u32>) {
+        if self.delta {
+            coder.decode_deltas_to_vec(0, self.tags, self.data, values);
+        } else {
+            coder.decode_to_vec(self.tags, self.data, values);
+        }
+    }
+
+    /// Check that the tags describe exactly the data length recorded in the block header.
+    fn validate(&self) -> Result<(), Error> {
+        let expected = match self.coder {
+            CoderId::Coder0124 => Coder0124::new().data_len(self.tags),
+            CoderId::Coder1234 => Coder1234::new().data_len(self.tags),
+            CoderId::Coder1248 => return Err(Error::ElemMismatch(self.coder)),
+        };
+        if expected != self.data.len() {
+            return Err(Error::DataLenMismatch {
+                expected,
+                actual: self.data.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Parse the block of `len` values at the start of `bytes`.
+fn parse_block(bytes: &[u8], len: usize) -> Result<AdaptiveBlock<'_>, Error> {
+    if bytes.len() < BLOCK_HEADER_LEN {
+        return Err(Error::Truncated);
+    }
+    let coder = CoderId::try_from(bytes[0])?;
+    let flags = bytes[1];
+    if flags & !FLAG_DELTA != 0 {
+        return Err(Error::InvalidHeader("flags"));
+    }
+    let data_len = u16::from_le_bytes([bytes[2], bytes[3]]) as usize;
+    let tag_len = len.div_ceil(4);
+    let body = &bytes[BLOCK_HEADER_LEN..];
+    if body.len() < tag_len + data_len {
+        return Err(Error::Truncated);
+    }
+    Ok(AdaptiveBlock {
+        coder,
+        delta: flags & FLAG_DELTA != 0,
+        len,
+        tags: &body[..tag_len],
+        data: &body[tag_len..(tag_len + data_len)],
+    })
+}
+
+/// Encode `values` as a single block appended to `out`, choosing the coding with the smallest
+/// data length with `AutoCoder`. `buf` is used to pad the final group.
+fn encode_block(values: &[u32], buf: &mut Vec<u32>, out: &mut Vec<u8>) {
+    let coder0124 = Coder0124::new();
+    let coder1234 = Coder1234::new();
+    // Delta coded blocks start from zero so that each block decodes independently.
+    let (coder, delta) = AutoCoder::new().best_u32(values, 0);
+
+    let (groups, remainder) = values.split_at(values.len() & !3);
+    buf.clear();
+    buf.extend_from_slice(groups);
+    if !remainder.is_empty() {
+        buf.extend_from_slice(&pad_group(remainder, delta));
+    }
+
+    out.push(coder as u8);
+    out.push(if delta { FLAG_DELTA } else { 0 });
+    let len_offset = out.len();
+    out.extend_from_slice(&[0u8; 2]);
+    let mut tags = Vec::with_capacity(buf.len() / 4);
+    let mut data = vec![];
+    match (coder, delta) {
+        (CoderId::Coder0124, false) => coder0124.encode_into_vec(buf, &mut tags, &mut data),
+        (CoderId::Coder0124, true) => {
+            coder0124.encode_deltas_into_vec(0, buf, &mut tags, &mut data)
+        }
+        (_, false) => coder1234.encode_into_vec(buf, &mut tags, &mut data),
+        (_, true) => coder1234.encode_deltas_into_vec(0, buf, &mut tags, &mut data),
+    };
+    out[len_offset..(len_offset + 2)].copy_from_slice(&(data.len() as u16).to_le_bytes());
+    out.extend_from_slice(&tags);
+    out.extend_from_slice(&data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EncodedBlock;
+
+    /// Returns 4 runs of 300 values that each favour a different coding.
+    fn mixed_values() -> Vec<u32> {
+        let mut values = vec![0u32; 300];
+        values.extend((0..300u32).map(|v| v % 200 + 1));
+        values.extend((0..300u32).map(|v| (1 << 30) + v / 2));
+        values.extend((0..300u32).map(|v| if v % 2 == 0 { 0x00ff_ffff } else { 0xff00_0000 }));
+        values
+    }
+
+    #[test]
+    fn round_trip() {
+        let values = mixed_values();
+        for block_len in [4, 64, 256, 1000, 16380] {
+            let stream = AdaptiveStream::encode_with_block_len(&values, block_len);
+            assert_eq!(values.len(), stream.len());
+            assert_eq!(values.len().div_ceil(block_len), stream.num_blocks());
+            assert_eq!(values, stream.decode());
+            for (i, v) in values.iter().enumerate() {
+                assert_eq!(
+                    Some(*v),
+                    stream.get(i),
+                    "block_len={} index={}",
+                    block_len,
+                    i
+                );
+            }
+            assert_eq!(None, stream.get(values.len()));
+            for range in [0..0, 0..1, 3..9, 299..301, 250..1200, 0..1200, 1199..1200] {
+                let mut actual = vec![];
+                stream.decode_range(range.clone(), &mut actual);
+                assert_eq!(
+                    &values[range.clone()],
+                    &actual[..],
+                    "block_len={} range={:?}",
+                    block_len,
+                    range
+                );
+            }
+            assert_eq!(
+                stream.blocks().map(|b| b.data().len()).sum::<usize>(),
+                stream.data_len()
+            );
+
+            let parsed = AdaptiveStream::from_bytes(stream.as_bytes()).unwrap();
+            assert_eq!(stream, parsed);
+        }
+
+        for len in [0, 1, 3, 4, 5, 1199] {
+            let stream = AdaptiveStream::encode(&values[(1200 - len)..]);
+            assert_eq!(&values[(1200 - len)..], stream.decode());
+            assert_eq!(len == 0, stream.is_empty());
+        }
+    }
+
+    #[test]
+    fn adapts_per_block() {
+        let values = mixed_values();
+        let stream = AdaptiveStream::encode_with_block_len(&values, 300);
+        let modes = stream
+            .blocks()
+            .map(|b| (b.coder_id(), b.is_delta()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (CoderId::Coder0124, false),
+                (CoderId::Coder1234, false),
+                (CoderId::Coder0124, true),
+                (CoderId::Coder1234, false),
+            ],
+            modes
+        );
+    }
+
+    #[test]
+    fn smallest_candidate_per_block() {
+        let values = mixed_values();
+        for block_len in [4, 64, 300] {
+            let stream = AdaptiveStream::encode_with_block_len(&values, block_len);
+            for (block, chunk) in stream.blocks().zip(values.chunks(block_len)) {
+                let best = [
+                    EncodedBlock::encode(&Coder0124::new(), chunk),
+                    EncodedBlock::encode_deltas(&Coder0124::new(), 0, chunk),
+                    EncodedBlock::encode(&Coder1234::new(), chunk),
+                    EncodedBlock::encode_deltas(&Coder1234::new(), 0, chunk),
+                ]
+                .iter()
+                .map(|b| b.data().len())
+                .min()
+                .unwrap();
+                assert_eq!(best, block.data().len(), "block_len={}", block_len);
+            }
+        }
+    }
+
+    #[test]
+    fn smaller_than_single_coder() {
+        let values = mixed_values();
+        let stream = AdaptiveStream::encode_with_block_len(&values, 300);
+        let data_len = stream.blocks().map(|b| b.data().len()).sum::<usize>();
+        for len in [
+            Coder0124::new().compressed_len(&values),
+            Coder0124::new().compressed_len_deltas(0, &values),
+            Coder1234::new().compressed_len(&values),
+            Coder1234::new().compressed_len_deltas(0, &values),
+        ] {
+            assert!(data_len < len);
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let values = mixed_values();
+        let bytes = AdaptiveStream::encode(&values).as_bytes().to_vec();
+        assert_eq!(
+            Err(Error::Truncated),
+            AdaptiveStream::from_bytes(&bytes[..(bytes.len() - 1)])
+        );
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            Err(Error::InvalidHeader("trailing bytes")),
+            AdaptiveStream::from_bytes(&extended)
+        );
+
+        let mut bad = bytes.clone();
+        bad[0] = 2;
+        assert_eq!(
+            Err(Error::UnsupportedVersion(2)),
+            AdaptiveStream::from_bytes(&bad)
+        );
+        let mut bad = bytes.clone();
+        bad[4] = 3;
+        assert_eq!(
+            Err(Error::InvalidHeader("block_len")),
+            AdaptiveStream::from_bytes(&bad)
+        );
+        let mut bad = bytes.clone();
+        bad[HEADER_LEN] = CoderId::Coder1248 as u8;
+        assert_eq!(
+            Err(Error::ElemMismatch(CoderId::Coder1248)),
+            AdaptiveStream::from_bytes(&bad)
+        );
+        let mut bad = bytes.clone();
+        bad[HEADER_LEN + 1] = 0x2;
+        assert_eq!(
+            Err(Error::InvalidHeader("flags")),
+            AdaptiveStream::from_bytes(&bad)
+        );
+        let mut bad = bytes;
+        bad[HEADER_LEN + BLOCK_HEADER_LEN] ^= 0xff;
+        assert!(matches!(
+            AdaptiveStream::from_bytes(&bad),
+            Err(Error::DataLenMismatch { .. }) | Err(Error::Truncated)
+        ));
+    }
+}
@@ -3,3 +3,5 @@
 #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
 pub(crate) mod neon;
 pub(crate) mod shuffle;
+#[cfg(target_arch = "x86_64")]
+pub(crate) mod x86;
@@ -1,7 +1,9 @@
 use super::shuffle::{decode_shuffle_entry, encode_shuffle_entry};
 use std::arch::aarch64::{
-    uint32x4_t, vaddlvq_u8, vaddq_u32, vandq_u8, vdupq_n_u32, vdupq_n_u8, vextq_u32, vld1q_u64,
-    vld1q_u8, vqtbl1q_u8, vreinterpretq_u8_u64,
+    uint32x4_t, uint64x2_t, vaddlvq_u8, vaddq_u32, vaddvq_u32, vandq_u32, vandq_u8, vceqq_u32,
+    vceqq_u64, vcgeq_u32, vcgeq_u64, vdupq_n_u32, vdupq_n_u64, vdupq_n_u8, vextq_u32, vextq_u64,
+    vget_lane_u64, vld1q_u32, vld1q_u64, vld1q_u8, vmovn_high_u64, vmovn_u32, vmovn_u64, vorrq_u32,
+    vorrq_u64, vqtbl1q_u8, vreinterpret_u64_u16, vreinterpretq_u8_u64,
 };
 
 /// Generate a table that encodes `ENTRY_LEN / ELEM_LEN` input values to contiguous bytes based on
@@ -77,3 +79,65 @@ pub(crate) unsafe fn sum_deltas32(delta_base: uint32x4_t, deltas: uint32x4_t) ->
     let pa_pab_pbc_pbd = vaddq_u32(p, a_ab_bc_cd);
     vaddq_u32(pa_pab_pbc_pbd, z_z_a_ab)
 }
+
+/// Returns the index of the first lane of `group` that is greater than or equal to `target`.
+#[inline(always)]
+pub(crate) unsafe fn find_ge32(group: uint32x4_t, target: u32) -> Option<usize> {
+    // Narrow each 32-bit lane mask to 16 bits so that the whole mask fits in a u64.
+    let mask = vget_lane_u64::<0>(vreinterpret_u64_u16(vmovn_u32(vcgeq_u32(
+        group,
+        vdupq_n_u32(target),
+    ))));
+    if mask == 0 {
+        None
+    } else {
+        Some(mask.trailing_zeros() as usize / 16)
+    }
+}
+
+/// Returns the index of the first lane of the group stored in `lo` and `hi` that is greater than or
+/// equal to `target`.
+#[inline(always)]
+pub(crate) unsafe fn find_ge64(lo: uint64x2_t, hi: uint64x2_t, target: u64) -> Option<usize> {
+    let target = vdupq_n_u64(target);
+    // Narrow each 64-bit lane mask to 16 bits so that the whole mask fits in a u64.
+    let ge = vmovn_high_u64(vmovn_u64(vcgeq_u64(lo, target)), vcgeq_u64(hi, target));
+    let mask = vget_lane_u64::<0>(vreinterpret_u64_u16(vmovn_u32(ge)));
+    if mask == 0 {
+        None
+    } else {
+        Some(mask.trailing_zeros() as usize / 16)
+    }
+}
+
+/// Returns a bitmask with bit `i` set if lane `i` of `a` is equal to any lane of `b`.
+#[inline(always)]
+pub(crate) unsafe fn match_any32(a: uint32x4_t, b: uint32x4_t) -> u32 {
+    let m01 = vorrq_u32(vceqq_u32(a, b), vceqq_u32(a, vextq_u32(b, b, 1)));
+    let m23 = vorrq_u32(
+        vceqq_u32(a, vextq_u32(b, b, 2)),
+        vceqq_u32(a, vextq_u32(b, b, 3)),
+    );
+    const LANE_BITS: [u32; 4] = [1, 2, 4, 8];
+    vaddvq_u32(vandq_u32(
+        vorrq_u32(m01, m23),
+        vld1q_u32(LANE_BITS.as_ptr()),
+    ))
+}
+
+/// Returns a bitmask with bit `i` set if lane `i` of the group stored in `a` is equal to any lane
+/// of the group stored in `b`.
+#[inline(always)]
+pub(crate) unsafe fn match_any64(a: (uint64x2_t, uint64x2_t), b: (uint64x2_t, uint64x2_t)) -> u32 {
+    // Compare each register of a against both registers of b and both with their lanes swapped.
+    let b = [b.0, vextq_u64(b.0, b.0, 1), b.1, vextq_u64(b.1, b.1, 1)];
+    let any = |a: uint64x2_t| {
+        vorrq_u64(
+            vorrq_u64(vceqq_u64(a, b[0]), vceqq_u64(a, b[1])),
+            vorrq_u64(vceqq_u64(a, b[2]), vceqq_u64(a, b[3])),
+        )
+    };
+    let m = vmovn_high_u64(vmovn_u64(any(a.0)), any(a.1));
+    const LANE_BITS: [u32; 4] = [1, 2, 4, 8];
+    vaddvq_u32(vandq_u32(m, vld1q_u32(LANE_BITS.as_ptr())))
+}
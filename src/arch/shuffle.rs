@@ -1,7 +1,7 @@
-/// Architecture independent utility for generating shuffle tables.
-///
-/// x86_64 and aarch64 have similar shuffle instructions that can share some logic but may not be
-/// able to share exactly the same tables.
+//! Architecture independent utility for generating shuffle tables.
+//!
+//! x86_64 and aarch64 have similar shuffle instructions that can share some logic but may not be
+//! able to share exactly the same tables.
 
 /// Generate a constant value that can be used to encode `ENTRY_LEN / ELEM_LEN` input values based
 /// on `tag` and the `tag_len` distribution.
@@ -11,7 +11,7 @@ pub(crate) const fn encode_shuffle_entry<const ELEM_LEN: usize, const ENTRY_LEN:
     tag_len: [usize; 4],
     fill_byte: u8,
 ) -> [u8; ENTRY_LEN] {
-    if ENTRY_LEN % ELEM_LEN != 0 {
+    if !ENTRY_LEN.is_multiple_of(ELEM_LEN) {
         panic!("ENTRY_LEN must divide evenly by ELEM_LEN")
     }
     let num_values = ENTRY_LEN / ELEM_LEN;
@@ -39,7 +39,7 @@ pub(crate) const fn decode_shuffle_entry<const ELEM_LEN: usize, const ENTRY_LEN:
     tag_len: [usize; 4],
     fill_byte: u8,
 ) -> [u8; ENTRY_LEN] {
-    if ENTRY_LEN % ELEM_LEN != 0 {
+    if !ENTRY_LEN.is_multiple_of(ELEM_LEN) {
         panic!("ENTRY_LEN must divide evenly by ELEM_LEN")
     }
     let num_values = ENTRY_LEN / ELEM_LEN;
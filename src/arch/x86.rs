@@ -0,0 +1,105 @@
+//! x86_64 utilities shared by the SSE implementations of each coder.
+//!
+//! These only use SSE2 instructions, which are always available on x86_64.
+
+use std::arch::x86_64::{
+    __m128i, _mm_and_si128, _mm_castsi128_pd, _mm_castsi128_ps, _mm_cmpeq_epi32, _mm_cmplt_epi32,
+    _mm_movemask_pd, _mm_movemask_ps, _mm_or_si128, _mm_set1_epi32, _mm_set1_epi64x,
+    _mm_shuffle_epi32, _mm_xor_si128,
+};
+
+/// Returns the index of the first bit set in the 4-bit lane mask `ge`.
+#[inline(always)]
+fn first_lane(ge: i32) -> Option<usize> {
+    if ge == 0 {
+        None
+    } else {
+        Some(ge.trailing_zeros() as usize)
+    }
+}
+
+/// Returns the index of the first lane of `group` that is greater than or equal to `target`.
+#[inline(always)]
+pub(crate) unsafe fn find_ge32(group: __m128i, target: u32) -> Option<usize> {
+    // There is no unsigned 32-bit compare so flip the sign bit of both operands.
+    let bias = _mm_set1_epi32(i32::MIN);
+    let lt = _mm_cmplt_epi32(
+        _mm_xor_si128(group, bias),
+        _mm_xor_si128(_mm_set1_epi32(target as i32), bias),
+    );
+    first_lane(!_mm_movemask_ps(_mm_castsi128_ps(lt)) & 0xf)
+}
+
+/// Returns a 2-bit mask with bit `i` set if 64-bit lane `i` of `a` is less than the same lane of
+/// `b`, where both operands have had the sign bit of each 32-bit half flipped.
+#[inline(always)]
+unsafe fn lt64_biased(a: __m128i, b: __m128i) -> i32 {
+    let lt = _mm_cmplt_epi32(a, b);
+    let eq = _mm_cmpeq_epi32(a, b);
+    // A lane is less if its high half is less, or equal with a lesser low half.
+    let hi_lt = _mm_shuffle_epi32(lt, 0b11_11_01_01);
+    let hi_eq = _mm_shuffle_epi32(eq, 0b11_11_01_01);
+    let lo_lt = _mm_shuffle_epi32(lt, 0b10_10_00_00);
+    _mm_movemask_pd(_mm_castsi128_pd(_mm_or_si128(
+        hi_lt,
+        _mm_and_si128(hi_eq, lo_lt),
+    )))
+}
+
+/// Returns the index of the first lane of the group stored in `lo` and `hi` that is greater than or
+/// equal to `target`.
+#[inline(always)]
+pub(crate) unsafe fn find_ge64(lo: __m128i, hi: __m128i, target: u64) -> Option<usize> {
+    // There is no 64-bit compare in SSE2 so compare the 32-bit halves and combine them.
+    let bias = _mm_set1_epi32(i32::MIN);
+    let target = _mm_xor_si128(_mm_set1_epi64x(target as i64), bias);
+    let lt = lt64_biased(_mm_xor_si128(lo, bias), target)
+        | (lt64_biased(_mm_xor_si128(hi, bias), target) << 2);
+    first_lane(!lt & 0xf)
+}
+
+/// Returns a bitmask with bit `i` set if lane `i` of `a` is equal to any lane of `b`.
+#[inline(always)]
+pub(crate) unsafe fn match_any32(a: __m128i, b: __m128i) -> u32 {
+    // Compare a against every rotation of b.
+    let m01 = _mm_or_si128(
+        _mm_cmpeq_epi32(a, b),
+        _mm_cmpeq_epi32(a, _mm_shuffle_epi32(b, 0b00_11_10_01)),
+    );
+    let m23 = _mm_or_si128(
+        _mm_cmpeq_epi32(a, _mm_shuffle_epi32(b, 0b01_00_11_10)),
+        _mm_cmpeq_epi32(a, _mm_shuffle_epi32(b, 0b10_01_00_11)),
+    );
+    _mm_movemask_ps(_mm_castsi128_ps(_mm_or_si128(m01, m23))) as u32
+}
+
+/// Returns a mask with each 64-bit lane set if the same lanes of `a` and `b` are equal.
+#[inline(always)]
+unsafe fn cmpeq64(a: __m128i, b: __m128i) -> __m128i {
+    // Both 32-bit halves of a lane must be equal.
+    let eq = _mm_cmpeq_epi32(a, b);
+    _mm_and_si128(eq, _mm_shuffle_epi32(eq, 0b10_11_00_01))
+}
+
+/// Returns a 2-bit mask with bit `i` set if 64-bit lane `i` of `a` is equal to any lane of the
+/// group stored in `b`.
+#[inline(always)]
+unsafe fn match_any64_half(a: __m128i, b: (__m128i, __m128i)) -> u32 {
+    // Compare a against both registers of b and both registers with their lanes swapped.
+    let m0 = _mm_or_si128(
+        cmpeq64(a, b.0),
+        cmpeq64(a, _mm_shuffle_epi32(b.0, 0b01_00_11_10)),
+    );
+    let m1 = _mm_or_si128(
+        cmpeq64(a, b.1),
+        cmpeq64(a, _mm_shuffle_epi32(b.1, 0b01_00_11_10)),
+    );
+    _mm_movemask_pd(_mm_castsi128_pd(_mm_or_si128(m0, m1))) as u32
+}
+
+/// Returns a bitmask with bit `i` set if lane `i` of the group stored in `a` is equal to any lane
+/// of the group stored in `b`.
+#[inline(always)]
+pub(crate) unsafe fn match_any64(a: (__m128i, __m128i), b: (__m128i, __m128i)) -> u32 {
+    match_any64_half(a.0, b) | (match_any64_half(a.1, b) << 2)
+}
@@ -0,0 +1,276 @@
+//! Automatic selection of a coder and delta mode based on the values being encoded.
+
+use crate::block::pad_group;
+use crate::{
+    BlockElem, Coder, Coder0124, Coder1234, Coder1248, CoderId, EncodedBlock, EncodedBlockRef,
+    Error,
+};
+use num_traits::Zero;
+use private::AutoElem;
+
+/// Number of values in each window examined when sampling.
+const SAMPLE_WINDOW: usize = 64;
+
+/// Encodes values with whichever coder and mode produces the smallest output.
+///
+/// Every coder that supports the element type is considered, both with and without delta coding,
+/// and the exact data length of each candidate is computed with `Coder::compressed_len()` and
+/// `Coder::compressed_len_deltas()`. The winning candidate is used to produce an `EncodedBlock`,
+/// whose header records the choice so that `EncodedBlockRef::decode()` dispatches to the right
+/// coder. Ties prefer `Coder1234` over `Coder0124` and plain over delta coding.
+///
+/// By default all values are scanned. `with_sample_len()` limits the scan to evenly spaced windows
+/// of the input, which is cheaper for long inputs but may choose a suboptimal coding.
+///
+/// ```
+/// use streamvbyte64::{AutoCoder, CoderId};
+///
+/// let values = (0..1000u32).map(|v| v * 3).collect::<Vec<_>>();
+/// let block = AutoCoder::new().encode(&values);
+/// assert_eq!(CoderId::Coder0124, block.coder_id());
+/// assert!(block.is_delta());
+/// assert_eq!(values, AutoCoder::decode::<u32>(&block.to_bytes()).unwrap());
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AutoCoder {
+    sample_len: Option<usize>,
+}
+
+impl AutoCoder {
+    /// Create an `AutoCoder` that scans all values to choose a coding.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an `AutoCoder` that estimates the size of each candidate from about `sample_len`
+    /// values spread evenly across the input.
+    ///
+    /// # Panics
+    ///
+    /// - If `sample_len == 0`.
+    pub fn with_sample_len(sample_len: usize) -> Self {
+        assert!(sample_len > 0);
+        Self {
+            sample_len: Some(sample_len),
+        }
+    }
+
+    /// Encode `values` with the coder and mode that produce the smallest output.
+    ///
+    /// This is implemented for the same element types as `BlockElem`.
+    pub fn encode<T: AutoElem>(&self, values: &[T]) -> EncodedBlock {
+        T::auto_encode(self, values)
+    }
+
+    /// Decode a block serialized with `EncodedBlock::to_bytes()`.
+    pub fn decode<T: BlockElem>(bytes: &[u8]) -> Result<Vec<T>, Error> {
+        EncodedBlockRef::from_bytes(bytes)?.decode()
+    }
+
+    /// Returns the data length (or sampled data length) of `values` encoded with `coder`, delta
+    /// coded from `initial` if set.
+    ///
+    /// A trailing partial group is padded the same way `EncodedBlock` pads it and always counted
+    /// in full, as the cost of the padding differs between coders and modes.
+    fn estimate<C: Coder>(&self, coder: &C, values: &[C::Elem], initial: Option<C::Elem>) -> usize {
+        let delta = initial.is_some();
+        let initial = initial.unwrap_or_else(C::Elem::zero);
+        let (groups, remainder) = values.split_at(values.len() & !3);
+        let len = |base: C::Elem, window: &[C::Elem]| {
+            if delta {
+                coder.compressed_len_deltas(base, window)
+            } else {
+                coder.compressed_len(window)
+            }
+        };
+        let groups_len = match self.sample_len {
+            Some(sample_len) if sample_len < groups.len() => {
+                let windows = sample_len.div_ceil(SAMPLE_WINDOW);
+                let stride = groups.len() / windows;
+                (0..windows)
+                    .map(|i| {
+                        let start = (i * stride) & !3;
+                        let end = std::cmp::min(start + SAMPLE_WINDOW, groups.len());
+                        let base = start.checked_sub(1).map_or(initial, |p| groups[p]);
+                        len(base, &groups[start..end])
+                    })
+                    .sum()
+            }
+            _ => len(initial, groups),
+        };
+        if remainder.is_empty() {
+            return groups_len;
+        }
+        let base = groups.last().copied().unwrap_or(initial);
+        groups_len + len(base, &pad_group(remainder, delta))
+    }
+
+    /// Returns the estimated data length and delta mode of the best coding of `values` with
+    /// `coder`, where delta coding starts from `initial`. Ties prefer plain coding.
+    fn best<C: Coder>(&self, coder: &C, values: &[C::Elem], initial: C::Elem) -> (usize, bool) {
+        [None, Some(initial)]
+            .into_iter()
+            .map(|i| (self.estimate(coder, values, i), i.is_some()))
+            .min_by_key(|(len, _)| *len)
+            .unwrap()
+    }
+
+    /// Returns the `u32` coder and delta mode with the smallest estimated data length for
+    /// `values`, where delta coding starts from `initial`. Ties prefer `Coder1234`.
+    pub(crate) fn best_u32(&self, values: &[u32], initial: u32) -> (CoderId, bool) {
+        let (len0124, delta0124) = self.best(&Coder0124::new(), values, initial);
+        let (len1234, delta1234) = self.best(&Coder1234::new(), values, initial);
+        if len0124 < len1234 {
+            (CoderId::Coder0124, delta0124)
+        } else {
+            (CoderId::Coder1234, delta1234)
+        }
+    }
+}
+
+mod private {
+    use crate::{AutoCoder, BlockElem, EncodedBlock};
+
+    /// Element types that `AutoCoder` can encode, choosing between the coders for each type.
+    pub trait AutoElem: BlockElem {
+        fn auto_encode(auto: &AutoCoder, values: &[Self]) -> EncodedBlock;
+    }
+}
+
+impl AutoElem for u32 {
+    fn auto_encode(auto: &AutoCoder, values: &[Self]) -> EncodedBlock {
+        match auto.best_u32(values, delta_initial::<Coder1234>(values)) {
+            (CoderId::Coder0124, delta) => encode_block(&Coder0124::new(), delta, values),
+            (_, delta) => encode_block(&Coder1234::new(), delta, values),
+        }
+    }
+}
+
+impl AutoElem for u64 {
+    fn auto_encode(auto: &AutoCoder, values: &[Self]) -> EncodedBlock {
+        let initial = delta_initial::<Coder1248>(values);
+        let (_, delta) = auto.best(&Coder1248::new(), values, initial);
+        encode_block(&Coder1248::new(), delta, values)
+    }
+}
+
+/// Returns the initial value used when delta coding `values`.
+fn delta_initial<C: Coder>(values: &[C::Elem]) -> C::Elem {
+    values.first().copied().unwrap_or_else(C::Elem::zero)
+}
+
+/// Encode `values` with `coder` as deltas if `delta` is set.
+fn encode_block<C: Coder>(coder: &C, delta: bool, values: &[C::Elem]) -> EncodedBlock {
+    if delta {
+        EncodedBlock::encode_deltas(coder, delta_initial::<C>(values), values)
+    } else {
+        EncodedBlock::encode(coder, values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{generate_array, generate_cumulative_array};
+    use crate::{Coder0124, Coder1234, CoderId};
+
+    #[test]
+    fn chooses_smallest() {
+        let auto = AutoCoder::new();
+
+        let zeros = vec![0u32; 1000];
+        let block = auto.encode(&zeros);
+        assert_eq!(
+            (CoderId::Coder0124, false),
+            (block.coder_id(), block.is_delta())
+        );
+
+        let large = (0..1000u32)
+            .map(|v| {
+                if v % 2 == 0 {
+                    0x00ff_ffffu32
+                } else {
+                    0xff00_0000
+                }
+            })
+            .collect::<Vec<_>>();
+        let block = auto.encode(&large);
+        assert_eq!(
+            (CoderId::Coder1234, false),
+            (block.coder_id(), block.is_delta())
+        );
+
+        let sorted = generate_cumulative_array::<u32>(1000, 2, 1 << 30);
+        let block = auto.encode(&sorted);
+        assert!(block.is_delta());
+
+        let sorted = generate_cumulative_array::<u64>(1000, 2, 1 << 40);
+        let block = auto.encode(&sorted);
+        assert_eq!(
+            (CoderId::Coder1248, true),
+            (block.coder_id(), block.is_delta())
+        );
+    }
+
+    #[test]
+    fn smallest_of_all_candidates() {
+        for max_bytes in [1, 2, 3, 4] {
+            let values = generate_cumulative_array::<u32>(1003, max_bytes, 5);
+            let block = AutoCoder::new().encode(&values);
+            let best = [
+                encode_block(&Coder0124::new(), false, &values),
+                encode_block(&Coder0124::new(), true, &values),
+                encode_block(&Coder1234::new(), false, &values),
+                encode_block(&Coder1234::new(), true, &values),
+            ]
+            .iter()
+            .map(|b| b.data().len())
+            .min()
+            .unwrap();
+            assert_eq!(best, block.data().len(), "max_bytes={}", max_bytes);
+            assert_eq!(values, block.decode::<u32>().unwrap());
+        }
+    }
+
+    #[test]
+    fn sampled() {
+        let mut values = vec![0u32; 4096];
+        values.extend(generate_array::<u32>(4096, 4));
+        let auto = AutoCoder::with_sample_len(256);
+        let block = auto.encode(&values);
+        // Zeros are free under Coder0124, which outweighs its extra cost on the random values.
+        assert_eq!(
+            (CoderId::Coder0124, false),
+            (block.coder_id(), block.is_delta())
+        );
+        let full = AutoCoder::new().encode(&values);
+        assert_eq!(
+            (full.coder_id(), full.is_delta()),
+            (block.coder_id(), block.is_delta())
+        );
+        assert_eq!(values, AutoCoder::decode::<u32>(&block.to_bytes()).unwrap());
+        // Short inputs are scanned in full.
+        let block = auto.encode(&values[..128]);
+        assert_eq!(CoderId::Coder0124, block.coder_id());
+    }
+
+    #[test]
+    fn trailing_group() {
+        // Full groups cost the same under both coders, so the padded final group decides: zero
+        // padding is free under Coder0124 but costs a byte per value under Coder1234.
+        let values = [0x100u32, 0x200, 0x100, 0x200, 0x300];
+        let block = AutoCoder::new().encode(&values);
+        assert_eq!(
+            (CoderId::Coder0124, false),
+            (block.coder_id(), block.is_delta())
+        );
+        assert_eq!(values.to_vec(), block.decode::<u32>().unwrap());
+    }
+
+    #[test]
+    fn empty() {
+        let block = AutoCoder::new().encode::<u64>(&[]);
+        assert!(block.is_empty());
+        assert_eq!(Vec::<u64>::new(), block.decode::<u64>().unwrap());
+    }
+}
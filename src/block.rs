@@ -0,0 +1,673 @@
+//! Self-describing container holding one encoded stream in a single byte buffer.
+
+use crate::{Coder, Coder0124, Coder1234, Coder1248, CoderId, Error};
+use num_traits::{NumCast, PrimInt, ToPrimitive, Zero};
+
+/// Version of the serialized block format written by this library.
+const FORMAT_VERSION: u8 = 1;
+/// Length of the serialized block header in bytes, excluding the optional checksum.
+const HEADER_LEN: usize = 36;
+/// Length of the checksum that follows the header if `FLAG_CHECKSUM` is set.
+const CHECKSUM_LEN: usize = 4;
+/// Header flag set when values are delta coded from `initial`.
+const FLAG_DELTA: u8 = 0x1;
+/// Header flag set when a CRC32C of the header, tag and data streams follows the header.
+const FLAG_CHECKSUM: u8 = 0x2;
+
+/// Compute the CRC32C of the serialized header fields of a block with a checksum, followed by the
+/// tag stream and the data stream.
+fn checksum(header: &Header, tags: &[u8], data: &[u8]) -> u32 {
+    debug_assert!(header.checksum.is_some());
+    let mut fields = Vec::with_capacity(HEADER_LEN);
+    header.write_fields(data.len(), &mut fields);
+    let crc = crc32c::crc32c_append(crc32c::crc32c(&fields), tags);
+    crc32c::crc32c_append(crc, data)
+}
+
+/// Metadata describing an encoded block.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Header {
+    coder: CoderId,
+    delta: bool,
+    len: usize,
+    initial: u64,
+    checksum: Option<u32>,
+}
+
+impl Header {
+    fn tag_len(&self) -> usize {
+        self.len.div_ceil(4)
+    }
+
+    /// Returns the length of the serialized header.
+    fn serialized_len(&self) -> usize {
+        HEADER_LEN + self.checksum.map_or(0, |_| CHECKSUM_LEN)
+    }
+
+    /// Serialize the header for a block containing `data_len` bytes of data to `out`.
+    fn write(&self, data_len: usize, out: &mut Vec<u8>) {
+        self.write_fields(data_len, out);
+        if let Some(c) = self.checksum {
+            out.extend_from_slice(&c.to_le_bytes());
+        }
+    }
+
+    /// Serialize the `HEADER_LEN` bytes of the header that precede the checksum to `out`.
+    fn write_fields(&self, data_len: usize, out: &mut Vec<u8>) {
+        out.push(FORMAT_VERSION);
+        out.push(self.coder as u8);
+        let mut flags = 0;
+        if self.delta {
+            flags |= FLAG_DELTA;
+        }
+        if self.checksum.is_some() {
+            flags |= FLAG_CHECKSUM;
+        }
+        out.push(flags);
+        out.push(0);
+        out.extend_from_slice(&(self.len as u64).to_le_bytes());
+        out.extend_from_slice(&self.initial.to_le_bytes());
+        out.extend_from_slice(&(self.tag_len() as u64).to_le_bytes());
+        out.extend_from_slice(&(data_len as u64).to_le_bytes());
+    }
+
+    /// Parse a header from the start of `bytes`, returning it with tag and data lengths.
+    /// Use `serialized_len()` to find the start of the tag stream.
+    fn read(bytes: &[u8]) -> Result<(Header, usize, usize), Error> {
+        if bytes.len() < HEADER_LEN {
+            return Err(Error::Truncated);
+        }
+        let read_u64 = |offset: usize| {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(&bytes[offset..(offset + 8)]);
+            u64::from_le_bytes(buf)
+        };
+        let read_usize = |offset: usize, field: &'static str| {
+            usize::try_from(read_u64(offset)).map_err(|_| Error::InvalidHeader(field))
+        };
+
+        if bytes[0] != FORMAT_VERSION {
+            return Err(Error::UnsupportedVersion(bytes[0]));
+        }
+        let coder = CoderId::try_from(bytes[1])?;
+        let flags = bytes[2];
+        if flags & !(FLAG_DELTA | FLAG_CHECKSUM) != 0 {
+            return Err(Error::InvalidHeader("flags"));
+        }
+        if bytes[3] != 0 {
+            return Err(Error::InvalidHeader("reserved"));
+        }
+        let checksum = if flags & FLAG_CHECKSUM != 0 {
+            let crc = bytes
+                .get(HEADER_LEN..(HEADER_LEN + CHECKSUM_LEN))
+                .ok_or(Error::Truncated)?;
+            Some(u32::from_le_bytes(crc.try_into().unwrap()))
+        } else {
+            None
+        };
+        let header = Header {
+            coder,
+            delta: flags & FLAG_DELTA != 0,
+            len: read_usize(4, "len")?,
+            initial: read_u64(12),
+            checksum,
+        };
+        if coder != CoderId::Coder1248 && header.initial > u32::MAX as u64 {
+            return Err(Error::InvalidHeader("initial"));
+        }
+        let tag_len = read_usize(20, "tag_len")?;
+        if tag_len != header.tag_len() {
+            return Err(Error::InvalidHeader("tag_len"));
+        }
+        let data_len = read_usize(28, "data_len")?;
+        Ok((header, tag_len, data_len))
+    }
+}
+
+/// Pad a trailing partial group of 1 to 3 values to a full group.
+///
+/// Delta coded groups repeat the last value so that the padding encodes as zero deltas; other
+/// groups are padded with zeros.
+pub(crate) fn pad_group<E: Copy + Zero>(remainder: &[E], delta: bool) -> [E; 4] {
+    let mut group = [if delta {
+        *remainder.last().unwrap()
+    } else {
+        E::zero()
+    }; 4];
+    group[..remainder.len()].copy_from_slice(remainder);
+    group
+}
+
+/// Encode `values` with `coder`, delta coding from `initial` if set.
+///
+/// If the number of values is not a multiple of 4 the final group is padded with `pad_group()`.
+fn encode_values<C: Coder>(
+    coder: &C,
+    initial: Option<C::Elem>,
+    values: &[C::Elem],
+) -> (Vec<u8>, Vec<u8>) {
+    let (tag_len, data_len) = C::max_compressed_bytes(values.len());
+    let mut tags = vec![0u8; tag_len];
+    let mut data = vec![0u8; data_len];
+    let (groups, remainder) = values.split_at(values.len() & !3);
+    let mut written = match initial {
+        Some(i) => coder.encode_deltas(i, groups, &mut tags, &mut data),
+        None => coder.encode(groups, &mut tags, &mut data),
+    };
+    if !remainder.is_empty() {
+        let group_tags = &mut tags[(groups.len() / 4)..];
+        let last = pad_group(remainder, initial.is_some());
+        written += match initial {
+            Some(i) => {
+                let base = groups.last().copied().unwrap_or(i);
+                coder.encode_deltas(base, &last, group_tags, &mut data[written..])
+            }
+            None => coder.encode(&last, group_tags, &mut data[written..]),
+        };
+    }
+    data.truncate(written);
+    (tags, data)
+}
+
+/// An owned block of encoded values that records everything needed to decode it.
+///
+/// Blocks track the coder used to produce them, the number of values, whether the values were
+/// delta coded and from what initial value. Any number of values may be encoded; a trailing
+/// partial group is padded and the padding is discarded during decoding.
+///
+/// ```
+/// use streamvbyte64::{Coder, Coder1234, EncodedBlock, EncodedBlockRef};
+///
+/// let values = (0..10u32).map(|v| v * 100).collect::<Vec<_>>();
+/// let block = EncodedBlock::encode_deltas(&Coder1234::new(), 0, &values);
+/// let bytes = block.to_bytes();
+///
+/// let parsed = EncodedBlockRef::from_bytes(&bytes).unwrap();
+/// assert_eq!(values, parsed.decode::<u32>().unwrap());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncodedBlock {
+    header: Header,
+    tags: Vec<u8>,
+    data: Vec<u8>,
+}
+
+impl EncodedBlock {
+    /// Encode `values` using `coder`.
+    pub fn encode<C: Coder>(coder: &C, values: &[C::Elem]) -> Self {
+        let (tags, data) = encode_values(coder, None, values);
+        Self {
+            header: Header {
+                coder: C::ID,
+                delta: false,
+                len: values.len(),
+                initial: 0,
+                checksum: None,
+            },
+            tags,
+            data,
+        }
+    }
+
+    /// Encode `values` as deltas starting from `initial` using `coder`.
+    pub fn encode_deltas<C: Coder>(coder: &C, initial: C::Elem, values: &[C::Elem]) -> Self {
+        let (tags, data) = encode_values(coder, Some(initial), values);
+        Self {
+            header: Header {
+                coder: C::ID,
+                delta: true,
+                len: values.len(),
+                initial: initial.to_u64().unwrap(),
+                checksum: None,
+            },
+            tags,
+            data,
+        }
+    }
+
+    /// Compute and record a CRC32C checksum of the header, tag and data streams.
+    ///
+    /// The checksum is serialized with the block and verified by every decode, so that corruption
+    /// is reported as `Error::ChecksumMismatch` instead of producing incorrect values.
+    pub fn with_checksum(mut self) -> Self {
+        // The checksum covers the flags, which must record that a checksum is present.
+        self.header.checksum = Some(0);
+        self.header.checksum = Some(checksum(&self.header, &self.tags, &self.data));
+        self
+    }
+
+    /// Parse and copy a block serialized with `to_bytes()`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        EncodedBlockRef::from_bytes(bytes).map(|b| b.to_block())
+    }
+
+    /// Serialize this block to a single buffer.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.view().to_bytes()
+    }
+
+    /// Returns a borrowed view of this block.
+    pub fn view(&self) -> EncodedBlockRef<'_> {
+        EncodedBlockRef {
+            header: self.header,
+            tags: &self.tags,
+            data: &self.data,
+        }
+    }
+
+    /// Returns the coder used to encode this block.
+    pub fn coder_id(&self) -> CoderId {
+        self.header.coder
+    }
+
+    /// Returns the number of values in this block.
+    pub fn len(&self) -> usize {
+        self.header.len
+    }
+
+    /// Returns true if the block contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.header.len == 0
+    }
+
+    /// Returns true if values were delta coded.
+    pub fn is_delta(&self) -> bool {
+        self.header.delta
+    }
+
+    /// Returns the initial value for delta coding, widened to `u64`.
+    pub fn initial(&self) -> u64 {
+        self.header.initial
+    }
+
+    /// Returns the recorded checksum, if any.
+    pub fn checksum(&self) -> Option<u32> {
+        self.header.checksum
+    }
+
+    /// Returns the tag stream.
+    pub fn tags(&self) -> &[u8] {
+        &self.tags
+    }
+
+    /// Returns the data stream.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Decode all values in this block, dispatching to the coder that produced it.
+    pub fn decode<T: BlockElem>(&self) -> Result<Vec<T>, Error> {
+        self.view().decode()
+    }
+
+    /// Decode all values in this block and append them to `values`.
+    pub fn decode_into<T: BlockElem>(&self, values: &mut Vec<T>) -> Result<(), Error> {
+        self.view().decode_into(values)
+    }
+}
+
+/// A borrowed view of an encoded block, typically parsed from a serialized buffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EncodedBlockRef<'a> {
+    header: Header,
+    tags: &'a [u8],
+    data: &'a [u8],
+}
+
+impl<'a> EncodedBlockRef<'a> {
+    /// Parse a block serialized with `to_bytes()` without copying.
+    ///
+    /// This validates the header and lengths but does not read the tag stream; inconsistencies
+    /// between tags and data and checksum mismatches are reported when decoding.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, Error> {
+        let (header, tag_len, data_len) = Header::read(bytes)?;
+        let body = &bytes[header.serialized_len()..];
+        if body.len() < tag_len || body.len() - tag_len < data_len {
+            return Err(Error::Truncated);
+        }
+        if body.len() - tag_len > data_len {
+            return Err(Error::InvalidHeader("data_len"));
+        }
+        let (tags, data) = body.split_at(tag_len);
+        Ok(Self { header, tags, data })
+    }
+
+    /// Serialize this block to a single buffer.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(self.header.serialized_len() + self.tags.len() + self.data.len());
+        self.header.write(self.data.len(), &mut bytes);
+        bytes.extend_from_slice(self.tags);
+        bytes.extend_from_slice(self.data);
+        bytes
+    }
+
+    /// Copy this view into an owned block.
+    pub fn to_block(&self) -> EncodedBlock {
+        EncodedBlock {
+            header: self.header,
+            tags: self.tags.to_vec(),
+            data: self.data.to_vec(),
+        }
+    }
+
+    /// Returns the coder used to encode this block.
+    pub fn coder_id(&self) -> CoderId {
+        self.header.coder
+    }
+
+    /// Returns the number of values in this block.
+    pub fn len(&self) -> usize {
+        self.header.len
+    }
+
+    /// Returns true if the block contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.header.len == 0
+    }
+
+    /// Returns true if values were delta coded.
+    pub fn is_delta(&self) -> bool {
+        self.header.delta
+    }
+
+    /// Returns the initial value for delta coding, widened to `u64`.
+    pub fn initial(&self) -> u64 {
+        self.header.initial
+    }
+
+    /// Returns the recorded checksum, if any.
+    pub fn checksum(&self) -> Option<u32> {
+        self.header.checksum
+    }
+
+    /// Returns the tag stream.
+    pub fn tags(&self) -> &'a [u8] {
+        self.tags
+    }
+
+    /// Returns the data stream.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Decode all values in this block, dispatching to the coder that produced it.
+    pub fn decode<T: BlockElem>(&self) -> Result<Vec<T>, Error> {
+        let mut values = Vec::with_capacity(self.len());
+        self.decode_into(&mut values)?;
+        Ok(values)
+    }
+
+    /// Decode all values in this block and append them to `values`.
+    ///
+    /// If the block has a checksum it is verified first. On error `values` is left unmodified.
+    pub fn decode_into<T: BlockElem>(&self, values: &mut Vec<T>) -> Result<(), Error> {
+        self.verify()?;
+        T::decode_block(self, values)
+    }
+
+    /// Verify the checksum of this block, if it has one.
+    pub fn verify(&self) -> Result<(), Error> {
+        match self.header.checksum {
+            Some(expected) => {
+                let actual = checksum(&self.header, self.tags, self.data);
+                if expected == actual {
+                    Ok(())
+                } else {
+                    Err(Error::ChecksumMismatch { expected, actual })
+                }
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Decode this block with `coder`, which must match the coder recorded in the header.
+    fn decode_with<C: Coder>(&self, coder: C, values: &mut Vec<C::Elem>) -> Result<(), Error> {
+        debug_assert_eq!(C::ID, self.header.coder);
+        let expected = coder.data_len(self.tags);
+        if expected != self.data.len() {
+            return Err(Error::DataLenMismatch {
+                expected,
+                actual: self.data.len(),
+            });
+        }
+        let initial = <C::Elem as NumCast>::from(self.header.initial)
+            .ok_or(Error::InvalidHeader("initial"))?;
+
+        let start = values.len();
+        let (group_tags, last_tag) = self.tags.split_at(self.header.len / 4);
+        let read = if self.header.delta {
+            coder.decode_deltas_to_vec(initial, group_tags, self.data, values)
+        } else {
+            coder.decode_to_vec(group_tags, self.data, values)
+        };
+        if !last_tag.is_empty() {
+            let mut last = [C::Elem::zero(); 4];
+            if self.header.delta {
+                let base = values[start..].last().copied().unwrap_or(initial);
+                coder.decode_deltas(base, last_tag, &self.data[read..], &mut last);
+            } else {
+                coder.decode(last_tag, &self.data[read..], &mut last);
+            }
+            values.extend_from_slice(&last[..(self.header.len % 4)]);
+        }
+        Ok(())
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for u32 {}
+    impl Sealed for u64 {}
+}
+
+/// Element types that may be decoded from an `EncodedBlock`.
+///
+/// This is implemented for `u32` (`Coder0124` and `Coder1234`) and `u64` (`Coder1248`).
+pub trait BlockElem: PrimInt + sealed::Sealed {
+    #[doc(hidden)]
+    fn decode_block(block: &EncodedBlockRef<'_>, values: &mut Vec<Self>) -> Result<(), Error>;
+}
+
+impl BlockElem for u32 {
+    fn decode_block(block: &EncodedBlockRef<'_>, values: &mut Vec<Self>) -> Result<(), Error> {
+        match block.coder_id() {
+            CoderId::Coder0124 => block.decode_with(Coder0124::new(), values),
+            CoderId::Coder1234 => block.decode_with(Coder1234::new(), values),
+            id => Err(Error::ElemMismatch(id)),
+        }
+    }
+}
+
+impl BlockElem for u64 {
+    fn decode_block(block: &EncodedBlockRef<'_>, values: &mut Vec<Self>) -> Result<(), Error> {
+        match block.coder_id() {
+            CoderId::Coder1248 => block.decode_with(Coder1248::new(), values),
+            id => Err(Error::ElemMismatch(id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{generate_array, generate_cumulative_array};
+
+    fn round_trip<C: Coder>(coder: C)
+    where
+        C::Elem: BlockElem,
+    {
+        for len in [0, 1, 2, 3, 4, 5, 31, 32, 33, 1000, 1003] {
+            let values = generate_array::<C::Elem>(len, std::mem::size_of::<C::Elem>());
+            let block = EncodedBlock::encode(&coder, &values);
+            assert_eq!(C::ID, block.coder_id());
+            assert_eq!(len, block.len());
+            assert!(!block.is_delta());
+            let bytes = block.to_bytes();
+            let parsed = EncodedBlockRef::from_bytes(&bytes).unwrap();
+            assert_eq!(block.view(), parsed);
+            assert_eq!(values, parsed.decode::<C::Elem>().unwrap());
+
+            let initial = <C::Elem as NumCast>::from(7).unwrap();
+            let values = generate_cumulative_array::<C::Elem>(len, 2, initial);
+            let block = EncodedBlock::encode_deltas(&coder, initial, &values);
+            assert!(block.is_delta());
+            assert_eq!(7, block.initial());
+            let parsed = EncodedBlock::from_bytes(&block.to_bytes()).unwrap();
+            assert_eq!(block, parsed);
+            assert_eq!(values, parsed.decode::<C::Elem>().unwrap());
+        }
+    }
+
+    #[test]
+    fn round_trip_coder0124() {
+        round_trip(Coder0124::new());
+    }
+
+    #[test]
+    fn round_trip_coder1234() {
+        round_trip(Coder1234::new());
+    }
+
+    #[test]
+    fn round_trip_coder1248() {
+        round_trip(Coder1248::new());
+    }
+
+    #[test]
+    fn checksum() {
+        let values = generate_cumulative_array::<u32>(1001, 3, 5);
+        let block = EncodedBlock::encode_deltas(&Coder1234::new(), 5, &values);
+        assert_eq!(None, block.checksum());
+        let block = block.with_checksum();
+        assert!(block.checksum().is_some());
+        assert_eq!(Ok(()), block.view().verify());
+
+        let bytes = block.to_bytes();
+        assert_eq!(
+            HEADER_LEN + CHECKSUM_LEN,
+            block.view().header.serialized_len()
+        );
+        let parsed = EncodedBlock::from_bytes(&bytes).unwrap();
+        assert_eq!(block, parsed);
+        assert_eq!(values, parsed.decode::<u32>().unwrap());
+
+        // Flip bits in header fields that still parse: a bit of len that keeps the tag length,
+        // the delta flag and every bit of the 32-bit initial value.
+        let header_bits = [(4, 1), (2, 0)]
+            .into_iter()
+            .chain((12..16).flat_map(|i| (0..8).map(move |bit| (i, bit))));
+        for (i, bit) in header_bits {
+            let mut corrupt = bytes.clone();
+            corrupt[i] ^= 1 << bit;
+            let block = EncodedBlockRef::from_bytes(&corrupt).unwrap();
+            assert!(
+                matches!(block.decode::<u32>(), Err(Error::ChecksumMismatch { .. })),
+                "byte {} bit {}",
+                i,
+                bit
+            );
+        }
+
+        // Flip every bit in the tag and data streams.
+        for i in (HEADER_LEN + CHECKSUM_LEN)..bytes.len() {
+            for bit in 0..8 {
+                let mut corrupt = bytes.clone();
+                corrupt[i] ^= 1 << bit;
+                let block = EncodedBlockRef::from_bytes(&corrupt).unwrap();
+                assert!(
+                    matches!(block.decode::<u32>(), Err(Error::ChecksumMismatch { .. })),
+                    "byte {} bit {}",
+                    i,
+                    bit
+                );
+            }
+        }
+
+        assert_eq!(
+            Err(Error::Truncated),
+            EncodedBlockRef::from_bytes(&bytes[..(HEADER_LEN + 2)])
+        );
+    }
+
+    #[test]
+    fn decode_into_appends() {
+        let block = EncodedBlock::encode(&Coder1234::new(), &[1u32, 2, 3, 4, 5]);
+        let mut values = vec![0u32];
+        block.decode_into(&mut values).unwrap();
+        assert_eq!(vec![0u32, 1, 2, 3, 4, 5], values);
+    }
+
+    #[test]
+    fn elem_mismatch() {
+        let block = EncodedBlock::encode(&Coder1234::new(), &[1u32, 2, 3, 4]);
+        assert_eq!(
+            Err(Error::ElemMismatch(CoderId::Coder1234)),
+            block.decode::<u64>()
+        );
+        let block = EncodedBlock::encode(&Coder1248::new(), &[1u64, 2, 3, 4]);
+        assert_eq!(
+            Err(Error::ElemMismatch(CoderId::Coder1248)),
+            block.decode::<u32>()
+        );
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        let values = generate_array::<u32>(100, 4);
+        let bytes = EncodedBlock::encode(&Coder1234::new(), &values).to_bytes();
+        assert!(EncodedBlockRef::from_bytes(&bytes).is_ok());
+
+        assert_eq!(
+            Err(Error::Truncated),
+            EncodedBlockRef::from_bytes(&bytes[..(HEADER_LEN - 1)])
+        );
+        assert_eq!(
+            Err(Error::Truncated),
+            EncodedBlockRef::from_bytes(&bytes[..(bytes.len() - 1)])
+        );
+
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            Err(Error::InvalidHeader("data_len")),
+            EncodedBlockRef::from_bytes(&extended)
+        );
+
+        let mut corrupt = bytes.clone();
+        corrupt[0] = 2;
+        assert_eq!(
+            Err(Error::UnsupportedVersion(2)),
+            EncodedBlockRef::from_bytes(&corrupt)
+        );
+
+        let mut corrupt = bytes.clone();
+        corrupt[1] = 0;
+        assert_eq!(
+            Err(Error::UnknownCoder(0)),
+            EncodedBlockRef::from_bytes(&corrupt)
+        );
+
+        let mut corrupt = bytes.clone();
+        corrupt[2] = 0x80;
+        assert_eq!(
+            Err(Error::InvalidHeader("flags")),
+            EncodedBlockRef::from_bytes(&corrupt)
+        );
+
+        let mut corrupt = bytes.clone();
+        corrupt[4] = 200;
+        assert_eq!(
+            Err(Error::InvalidHeader("tag_len")),
+            EncodedBlockRef::from_bytes(&corrupt)
+        );
+
+        let mut corrupt = bytes.clone();
+        corrupt[HEADER_LEN] ^= 0xff;
+        let block = EncodedBlockRef::from_bytes(&corrupt).unwrap();
+        assert!(matches!(
+            block.decode::<u32>(),
+            Err(Error::DataLenMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,336 @@
+//! C ABI for the `Coder` implementations, enabled with the `capi` feature.
+//!
+//! Each coder exposes a family of `streamvbyte64_<coder>_<method>` functions that mirror the
+//! methods of `Coder`. Instead of panicking on invalid input these functions validate all lengths
+//! up front and return a `Streamvbyte64Status`; outputs are reported through pointer arguments.
+//! Pointers may only be null if the associated length is zero. Any panic is caught at the boundary
+//! and reported as `Streamvbyte64Status::Panic` rather than unwinding into the caller.
+//!
+//! The header `include/streamvbyte64.h` is generated from this module with `cbindgen`.
+
+use crate::{Coder, Coder0124, Coder1234, Coder1248};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Status codes returned by all C functions.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Streamvbyte64Status {
+    /// The call succeeded.
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = -1,
+    /// The number of values is not a multiple of 4.
+    InvalidLength = -2,
+    /// An output buffer is too small to hold the result.
+    BufferTooSmall = -3,
+    /// The data stream ends before all of the groups described by the tags.
+    Truncated = -4,
+    /// A length is too large to be represented.
+    Overflow = -5,
+    /// An unexpected internal error occurred.
+    Panic = -6,
+}
+
+unsafe fn input<'a, T>(ptr: *const T, len: usize) -> Result<&'a [T], Streamvbyte64Status> {
+    if len == 0 {
+        Ok(&[])
+    } else if ptr.is_null() {
+        Err(Streamvbyte64Status::NullPointer)
+    } else {
+        Ok(std::slice::from_raw_parts(ptr, len))
+    }
+}
+
+unsafe fn output<'a, T>(ptr: *mut T, len: usize) -> Result<&'a mut [T], Streamvbyte64Status> {
+    if len == 0 {
+        Ok(&mut [])
+    } else if ptr.is_null() {
+        Err(Streamvbyte64Status::NullPointer)
+    } else {
+        Ok(std::slice::from_raw_parts_mut(ptr, len))
+    }
+}
+
+unsafe fn store<T>(ptr: *mut T, value: T) -> Result<(), Streamvbyte64Status> {
+    if ptr.is_null() {
+        Err(Streamvbyte64Status::NullPointer)
+    } else {
+        *ptr = value;
+        Ok(())
+    }
+}
+
+/// Run `f`, converting its result or any panic into a status code.
+fn status(f: impl FnOnce() -> Result<(), Streamvbyte64Status>) -> Streamvbyte64Status {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => Streamvbyte64Status::Ok,
+        Ok(Err(s)) => s,
+        Err(_) => Streamvbyte64Status::Panic,
+    }
+}
+
+/// Returns `Coder::max_compressed_bytes(len)`, or an error if the data length would overflow.
+fn max_compressed_bytes<C: Coder>(len: usize) -> Result<(usize, usize), Streamvbyte64Status> {
+    len.div_ceil(4)
+        .checked_mul(4 * std::mem::size_of::<C::Elem>())
+        .ok_or(Streamvbyte64Status::Overflow)?;
+    Ok(C::max_compressed_bytes(len))
+}
+
+/// Check that `len` values may be encoded to `tags_len` and `data_len` bytes.
+fn check_encode<C: Coder>(
+    len: usize,
+    tags_len: usize,
+    data_len: usize,
+) -> Result<(), Streamvbyte64Status> {
+    if !len.is_multiple_of(4) {
+        return Err(Streamvbyte64Status::InvalidLength);
+    }
+    let (max_tags, max_data) = max_compressed_bytes::<C>(len)?;
+    if tags_len < max_tags || data_len < max_data {
+        return Err(Streamvbyte64Status::BufferTooSmall);
+    }
+    Ok(())
+}
+
+/// Check that `len` values may be decoded from `tags` and `data_len` bytes, returning the tags to
+/// decode.
+fn check_decode<C: Coder>(
+    coder: &C,
+    len: usize,
+    tags: &[u8],
+    data_len: usize,
+) -> Result<usize, Streamvbyte64Status> {
+    if !len.is_multiple_of(4) {
+        return Err(Streamvbyte64Status::InvalidLength);
+    }
+    if tags.len() < len / 4 {
+        return Err(Streamvbyte64Status::BufferTooSmall);
+    }
+    if coder.data_len(&tags[..(len / 4)]) > data_len {
+        return Err(Streamvbyte64Status::Truncated);
+    }
+    Ok(len / 4)
+}
+
+macro_rules! capi_coder {
+    (
+        $coder:ident,
+        $elem:ty,
+        $max_compressed_bytes:ident,
+        $encode:ident,
+        $encode_deltas:ident,
+        $decode:ident,
+        $decode_deltas:ident,
+        $data_len:ident,
+        $skip_deltas:ident
+    ) => {
+        /// Writes the number of tag and data bytes required to encode `len` values.
+        ///
+        /// # Safety
+        ///
+        /// `tags_len` and `data_len` must be valid for writes.
+        #[no_mangle]
+        pub unsafe extern "C" fn $max_compressed_bytes(
+            len: usize,
+            tags_len: *mut usize,
+            data_len: *mut usize,
+        ) -> Streamvbyte64Status {
+            status(|| {
+                let (t, d) = max_compressed_bytes::<$coder>(len)?;
+                store(tags_len, t)?;
+                store(data_len, d)
+            })
+        }
+
+        /// Encodes `len` values, writing the number of data bytes used to `written`.
+        ///
+        /// # Safety
+        ///
+        /// All pointers must be valid for the lengths provided alongside them.
+        #[no_mangle]
+        pub unsafe extern "C" fn $encode(
+            values: *const $elem,
+            len: usize,
+            tags: *mut u8,
+            tags_len: usize,
+            data: *mut u8,
+            data_len: usize,
+            written: *mut usize,
+        ) -> Streamvbyte64Status {
+            status(|| {
+                check_encode::<$coder>(len, tags_len, data_len)?;
+                let values = input(values, len)?;
+                let tags = output(tags, tags_len)?;
+                let data = output(data, data_len)?;
+                store(written, $coder::new().encode(values, tags, data))
+            })
+        }
+
+        /// Encodes `len` values as deltas from `initial`, writing the number of data bytes used
+        /// to `written`.
+        ///
+        /// # Safety
+        ///
+        /// All pointers must be valid for the lengths provided alongside them.
+        #[no_mangle]
+        pub unsafe extern "C" fn $encode_deltas(
+            initial: $elem,
+            values: *const $elem,
+            len: usize,
+            tags: *mut u8,
+            tags_len: usize,
+            data: *mut u8,
+            data_len: usize,
+            written: *mut usize,
+        ) -> Streamvbyte64Status {
+            status(|| {
+                check_encode::<$coder>(len, tags_len, data_len)?;
+                let values = input(values, len)?;
+                let tags = output(tags, tags_len)?;
+                let data = output(data, data_len)?;
+                store(
+                    written,
+                    $coder::new().encode_deltas(initial, values, tags, data),
+                )
+            })
+        }
+
+        /// Decodes `len` values, writing the number of data bytes consumed to `read`.
+        ///
+        /// # Safety
+        ///
+        /// All pointers must be valid for the lengths provided alongside them.
+        #[no_mangle]
+        pub unsafe extern "C" fn $decode(
+            tags: *const u8,
+            tags_len: usize,
+            data: *const u8,
+            data_len: usize,
+            values: *mut $elem,
+            len: usize,
+            read: *mut usize,
+        ) -> Streamvbyte64Status {
+            status(|| {
+                let coder = $coder::new();
+                let tags = input(tags, tags_len)?;
+                let ntags = check_decode(&coder, len, tags, data_len)?;
+                let data = input(data, data_len)?;
+                let values = output(values, len)?;
+                store(read, coder.decode(&tags[..ntags], data, values))
+            })
+        }
+
+        /// Decodes `len` values as deltas from `initial`, writing the number of data bytes
+        /// consumed to `read`.
+        ///
+        /// # Safety
+        ///
+        /// All pointers must be valid for the lengths provided alongside them.
+        #[no_mangle]
+        pub unsafe extern "C" fn $decode_deltas(
+            initial: $elem,
+            tags: *const u8,
+            tags_len: usize,
+            data: *const u8,
+            data_len: usize,
+            values: *mut $elem,
+            len: usize,
+            read: *mut usize,
+        ) -> Streamvbyte64Status {
+            status(|| {
+                let coder = $coder::new();
+                let tags = input(tags, tags_len)?;
+                let ntags = check_decode(&coder, len, tags, data_len)?;
+                let data = input(data, data_len)?;
+                let values = output(values, len)?;
+                store(
+                    read,
+                    coder.decode_deltas(initial, &tags[..ntags], data, values),
+                )
+            })
+        }
+
+        /// Writes the data length of all the groups encoded by `tags` to `len`.
+        ///
+        /// # Safety
+        ///
+        /// All pointers must be valid for the lengths provided alongside them.
+        #[no_mangle]
+        pub unsafe extern "C" fn $data_len(
+            tags: *const u8,
+            tags_len: usize,
+            len: *mut usize,
+        ) -> Streamvbyte64Status {
+            status(|| {
+                let tags = input(tags, tags_len)?;
+                store(len, $coder::new().data_len(tags))
+            })
+        }
+
+        /// Skips `tags_len * 4` deltas, writing the number of data bytes consumed to `read` and
+        /// the sum of the skipped deltas to `sum`.
+        ///
+        /// # Safety
+        ///
+        /// All pointers must be valid for the lengths provided alongside them.
+        #[no_mangle]
+        pub unsafe extern "C" fn $skip_deltas(
+            tags: *const u8,
+            tags_len: usize,
+            data: *const u8,
+            data_len: usize,
+            read: *mut usize,
+            sum: *mut $elem,
+        ) -> Streamvbyte64Status {
+            status(|| {
+                let len = tags_len
+                    .checked_mul(4)
+                    .ok_or(Streamvbyte64Status::Overflow)?;
+                let coder = $coder::new();
+                let tags = input(tags, tags_len)?;
+                check_decode(&coder, len, tags, data_len)?;
+                let data = input(data, data_len)?;
+                let (r, s) = coder.skip_deltas(tags, data);
+                store(read, r)?;
+                store(sum, s)
+            })
+        }
+    };
+}
+
+capi_coder!(
+    Coder0124,
+    u32,
+    streamvbyte64_coder0124_max_compressed_bytes,
+    streamvbyte64_coder0124_encode,
+    streamvbyte64_coder0124_encode_deltas,
+    streamvbyte64_coder0124_decode,
+    streamvbyte64_coder0124_decode_deltas,
+    streamvbyte64_coder0124_data_len,
+    streamvbyte64_coder0124_skip_deltas
+);
+
+capi_coder!(
+    Coder1234,
+    u32,
+    streamvbyte64_coder1234_max_compressed_bytes,
+    streamvbyte64_coder1234_encode,
+    streamvbyte64_coder1234_encode_deltas,
+    streamvbyte64_coder1234_decode,
+    streamvbyte64_coder1234_decode_deltas,
+    streamvbyte64_coder1234_data_len,
+    streamvbyte64_coder1234_skip_deltas
+);
+
+capi_coder!(
+    Coder1248,
+    u64,
+    streamvbyte64_coder1248_max_compressed_bytes,
+    streamvbyte64_coder1248_encode,
+    streamvbyte64_coder1248_encode_deltas,
+    streamvbyte64_coder1248_decode,
+    streamvbyte64_coder1248_decode_deltas,
+    streamvbyte64_coder1248_data_len,
+    streamvbyte64_coder1248_skip_deltas
+);
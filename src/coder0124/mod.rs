@@ -4,7 +4,7 @@ mod neon;
 mod sse41;
 
 use crate::coding_descriptor::CodingDescriptor;
-use crate::{coder_impl, Coder};
+use crate::{Coder, CoderId};
 
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct CodingDescriptor0124;
@@ -54,9 +54,13 @@ enum Impl {
 #[derive(Clone, Copy)]
 pub struct Coder0124(Impl);
 
+impl crate::private::Sealed for Coder0124 {}
+
 impl Coder for Coder0124 {
     type Elem = u32;
 
+    const ID: CoderId = CoderId::Coder0124;
+
     fn new() -> Self {
         #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
         {
@@ -77,89 +81,7 @@ impl Coder for Coder0124 {
         Coder0124(Impl::Scalar)
     }
 
-    fn encode(&self, values: &[u32], tags: &mut [u8], encoded: &mut [u8]) -> usize {
-        match self.0 {
-            Impl::Scalar => coder_impl::encode::<scalar::RawGroupImpl>(values, tags, encoded),
-            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
-            Impl::Neon => coder_impl::encode::<neon::RawGroupImpl>(values, tags, encoded),
-            #[cfg(target_arch = "x86_64")]
-            Impl::SSE41 => coder_impl::encode::<sse41::RawGroupImpl>(values, tags, encoded),
-        }
-    }
-
-    fn encode_deltas(
-        &self,
-        initial: u32,
-        values: &[u32],
-        tags: &mut [u8],
-        encoded: &mut [u8],
-    ) -> usize {
-        match self.0 {
-            Impl::Scalar => {
-                coder_impl::encode_deltas::<scalar::RawGroupImpl>(initial, values, tags, encoded)
-            }
-            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
-            Impl::Neon => {
-                coder_impl::encode_deltas::<neon::RawGroupImpl>(initial, values, tags, encoded)
-            }
-            #[cfg(target_arch = "x86_64")]
-            Impl::SSE41 => {
-                coder_impl::encode_deltas::<sse41::RawGroupImpl>(initial, values, tags, encoded)
-            }
-        }
-    }
-
-    fn decode(&self, tags: &[u8], encoded: &[u8], values: &mut [u32]) -> usize {
-        match self.0 {
-            Impl::Scalar => coder_impl::decode::<scalar::RawGroupImpl>(tags, encoded, values),
-            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
-            Impl::Neon => coder_impl::decode::<neon::RawGroupImpl>(tags, encoded, values),
-            #[cfg(target_arch = "x86_64")]
-            Impl::SSE41 => coder_impl::decode::<sse41::RawGroupImpl>(tags, encoded, values),
-        }
-    }
-
-    fn decode_deltas(
-        &self,
-        initial: u32,
-        tags: &[u8],
-        encoded: &[u8],
-        values: &mut [u32],
-    ) -> usize {
-        match self.0 {
-            Impl::Scalar => {
-                coder_impl::decode_deltas::<scalar::RawGroupImpl>(initial, tags, encoded, values)
-            }
-            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
-            Impl::Neon => {
-                coder_impl::decode_deltas::<neon::RawGroupImpl>(initial, tags, encoded, values)
-            }
-            #[cfg(target_arch = "x86_64")]
-            Impl::SSE41 => {
-                coder_impl::decode_deltas::<sse41::RawGroupImpl>(initial, tags, encoded, values)
-            }
-        }
-    }
-
-    fn data_len(&self, tags: &[u8]) -> usize {
-        match self.0 {
-            Impl::Scalar => coder_impl::data_len::<scalar::RawGroupImpl>(tags),
-            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
-            Impl::Neon => coder_impl::data_len::<neon::RawGroupImpl>(tags),
-            #[cfg(target_arch = "x86_64")]
-            Impl::SSE41 => coder_impl::data_len::<sse41::RawGroupImpl>(tags),
-        }
-    }
-
-    fn skip_deltas(&self, tags: &[u8], encoded: &[u8]) -> (usize, u32) {
-        match self.0 {
-            Impl::Scalar => coder_impl::skip_deltas::<scalar::RawGroupImpl>(tags, encoded),
-            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
-            Impl::Neon => coder_impl::skip_deltas::<neon::RawGroupImpl>(tags, encoded),
-            #[cfg(target_arch = "x86_64")]
-            Impl::SSE41 => coder_impl::skip_deltas::<sse41::RawGroupImpl>(tags, encoded),
-        }
-    }
+    crate::coder_impl::coder_methods!(u32, SSE41, sse41);
 }
 
 #[cfg(test)]
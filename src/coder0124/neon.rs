@@ -1,7 +1,9 @@
 use crunchy::unroll;
 
 use super::{scalar, CodingDescriptor0124};
-use crate::arch::neon::{data_len8, decode_shuffle_table, encode_shuffle_table, sum_deltas32};
+use crate::arch::neon::{
+    data_len8, decode_shuffle_table, encode_shuffle_table, find_ge32, match_any32, sum_deltas32,
+};
 use crate::coding_descriptor::CodingDescriptor;
 use crate::raw_group::RawGroup;
 use std::arch::aarch64::{
@@ -44,6 +46,34 @@ fn tag8_offsets(tag8: u64) -> (u64, usize) {
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct RawGroupImpl(uint32x4_t);
 
+impl RawGroupImpl {
+    #[inline(always)]
+    unsafe fn compute_tag(&self) -> (u8, usize) {
+        // Value tags are computed using the same algorithm as scalar but vector parallel.
+        let clz_bytes = vsubq_u32(vdupq_n_u32(4), vshrq_n_u32(vclzq_u32(self.0), 3));
+        let value_tags = vqtbl1q_u8(
+            vld1q_u8([0, 1, 2, 3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0].as_ptr()),
+            vreinterpretq_u8_u32(clz_bytes),
+        );
+        // Shift value tags so that they do not overlap and sum them to get the tag.
+        let tag = vaddvq_u32(vshlq_u32(
+            vreinterpretq_u32_u8(value_tags),
+            vld1q_s32([0, 2, 4, 6].as_ptr()),
+        )) as u8;
+        let written = vaddvq_u8(vqtbl1q_u8(
+            vld1q_u8([0, 1, 2, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0].as_ptr()),
+            value_tags,
+        )) as usize;
+        (tag, written)
+    }
+
+    /// Returns the difference between each element of group and the preceding element.
+    #[inline(always)]
+    unsafe fn deltas(base: Self, group: Self) -> Self {
+        RawGroupImpl(vsubq_u32(group.0, vextq_u32(base.0, group.0, 3)))
+    }
+}
+
 impl RawGroup for RawGroupImpl {
     type Elem = u32;
     const TAG_LEN: [usize; 4] = CodingDescriptor0124::TAG_LEN;
@@ -65,21 +95,7 @@ impl RawGroup for RawGroupImpl {
 
     #[inline]
     unsafe fn encode(output: *mut u8, group: Self) -> (u8, usize) {
-        // Value tags are computed using the same algorithm as scalar but vector parallel.
-        let clz_bytes = vsubq_u32(vdupq_n_u32(4), vshrq_n_u32(vclzq_u32(group.0), 3));
-        let value_tags = vqtbl1q_u8(
-            vld1q_u8([0, 1, 2, 3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0].as_ptr()),
-            vreinterpretq_u8_u32(clz_bytes),
-        );
-        // Shift value tags so that they do not overlap and sum them to get the tag.
-        let tag = vaddvq_u32(vshlq_u32(
-            vreinterpretq_u32_u8(value_tags),
-            vld1q_s32([0, 2, 4, 6].as_ptr()),
-        )) as u8;
-        let written = vaddvq_u8(vqtbl1q_u8(
-            vld1q_u8([0, 1, 2, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0].as_ptr()),
-            value_tags,
-        )) as usize;
+        let (tag, written) = group.compute_tag();
 
         // Use a precomputed table that shuffles all the bytes ientified by the tag as close together as possible.
         // This will write 16 bytes but everything beyond written will be 0.
@@ -96,8 +112,7 @@ impl RawGroup for RawGroupImpl {
 
     #[inline]
     unsafe fn encode_deltas(output: *mut u8, base: Self, group: Self) -> (u8, usize) {
-        let deltas = RawGroupImpl(vsubq_u32(group.0, vextq_u32(base.0, group.0, 3)));
-        Self::encode(output, deltas)
+        Self::encode(output, Self::deltas(base, group))
     }
 
     #[inline]
@@ -174,6 +189,16 @@ impl RawGroup for RawGroupImpl {
         scalar::RawGroupImpl::data_len(tag)
     }
 
+    #[inline]
+    fn tag(group: Self) -> u8 {
+        unsafe { group.compute_tag().0 }
+    }
+
+    #[inline]
+    fn tag_deltas(base: Self, group: Self) -> u8 {
+        Self::tag(unsafe { Self::deltas(base, group) })
+    }
+
     #[inline]
     unsafe fn skip_deltas(input: *const u8, tag: u8) -> (usize, u32) {
         let (read, group) = Self::decode(input, tag);
@@ -184,6 +209,16 @@ impl RawGroup for RawGroupImpl {
     fn data_len8(tag8: u64) -> usize {
         data_len8(Self::TAG_LEN, tag8)
     }
+
+    #[inline]
+    fn find_ge(group: Self, target: u32) -> Option<usize> {
+        unsafe { find_ge32(group.0, target) }
+    }
+
+    #[inline]
+    fn match_any(a: Self, b: Self) -> u32 {
+        unsafe { match_any32(a.0, b.0) }
+    }
 }
 
 #[cfg(test)]
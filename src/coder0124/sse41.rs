@@ -2,6 +2,7 @@ use crunchy::unroll;
 
 use super::{scalar, CodingDescriptor0124};
 use crate::arch::shuffle::decode_shuffle_entry;
+use crate::arch::x86::{find_ge32, match_any32};
 use crate::raw_group::RawGroup;
 use crate::{arch::shuffle::encode_shuffle_entry, coding_descriptor::CodingDescriptor};
 use std::arch::x86_64::{
@@ -54,6 +55,12 @@ impl RawGroupImpl {
         let d = _mm_packus_epi32(c, c);
         _mm_movemask_epi8(d) as u8
     }
+
+    /// Returns the difference between each element of group and the preceding element.
+    #[inline(always)]
+    unsafe fn deltas(base: Self, group: Self) -> Self {
+        Self(_mm_sub_epi32(group.0, _mm_alignr_epi8(group.0, base.0, 12)))
+    }
 }
 
 impl RawGroup for RawGroupImpl {
@@ -62,7 +69,7 @@ impl RawGroup for RawGroupImpl {
 
     #[inline]
     fn set1(value: Self::Elem) -> Self {
-        RawGroupImpl(unsafe { _mm_set1_epi32(std::mem::transmute(value)) })
+        RawGroupImpl(unsafe { _mm_set1_epi32(value as i32) })
     }
 
     #[inline]
@@ -92,10 +99,7 @@ impl RawGroup for RawGroupImpl {
 
     #[inline]
     unsafe fn encode_deltas(output: *mut u8, base: Self, group: Self) -> (u8, usize) {
-        Self::encode(
-            output,
-            Self(_mm_sub_epi32(group.0, _mm_alignr_epi8(group.0, base.0, 12))),
-        )
+        Self::encode(output, Self::deltas(base, group))
     }
 
     #[inline]
@@ -126,6 +130,16 @@ impl RawGroup for RawGroupImpl {
         scalar::RawGroupImpl::data_len(tag)
     }
 
+    #[inline]
+    fn tag(group: Self) -> u8 {
+        unsafe { group.compute_tag() }
+    }
+
+    #[inline]
+    fn tag_deltas(base: Self, group: Self) -> u8 {
+        Self::tag(unsafe { Self::deltas(base, group) })
+    }
+
     #[inline]
     unsafe fn skip_deltas(input: *const u8, tag: u8) -> (usize, Self::Elem) {
         let (len, deltas) = Self::decode(input, tag);
@@ -149,6 +163,16 @@ impl RawGroup for RawGroupImpl {
         _mm_storeu_si128(d.as_mut_ptr() as *mut __m128i, delta_sum.0);
         (offset, d.into_iter().fold(0, |s, d| s.wrapping_add(d)))
     }
+
+    #[inline]
+    fn find_ge(group: Self, target: u32) -> Option<usize> {
+        unsafe { find_ge32(group.0, target) }
+    }
+
+    #[inline]
+    fn match_any(a: Self, b: Self) -> u32 {
+        unsafe { match_any32(a.0, b.0) }
+    }
 }
 
 #[cfg(test)]
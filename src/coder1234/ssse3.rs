@@ -2,6 +2,7 @@ use crunchy::unroll;
 
 use super::{scalar, CodingDescriptor1234};
 use crate::arch::shuffle::decode_shuffle_entry;
+use crate::arch::x86::{find_ge32, match_any32};
 use crate::raw_group::RawGroup;
 use crate::{arch::shuffle::encode_shuffle_entry, coding_descriptor::CodingDescriptor};
 use std::arch::x86_64::{
@@ -41,13 +42,34 @@ const DECODE_TABLE: [[u8; 16]; 256] = {
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct RawGroupImpl(__m128i);
 
+impl RawGroupImpl {
+    #[inline(always)]
+    unsafe fn compute_tag(&self) -> u8 {
+        // This implementation for generating the tag byte came from https://github.com/lemire/streamvbyte/blob/08c60644dc6956182c68c1b453ba5f2d42367823/src/streamvbytedelta_x64_encode.c
+        let mask_01 = _mm_set1_epi8(0x1);
+        let mask_7f00 = _mm_set1_epi16(0x7f00);
+
+        let a = _mm_min_epu8(mask_01, self.0);
+        let b = _mm_packus_epi16(a, a);
+        let c = _mm_min_epi16(b, mask_01);
+        let d = _mm_adds_epu16(c, mask_7f00);
+        _mm_movemask_epi8(d) as u8
+    }
+
+    /// Returns the difference between each element of group and the preceding element.
+    #[inline(always)]
+    unsafe fn deltas(base: Self, group: Self) -> Self {
+        Self(_mm_sub_epi32(group.0, _mm_alignr_epi8(group.0, base.0, 12)))
+    }
+}
+
 impl RawGroup for RawGroupImpl {
     type Elem = u32;
     const TAG_LEN: [usize; 4] = CodingDescriptor1234::TAG_LEN;
 
     #[inline]
     fn set1(value: Self::Elem) -> Self {
-        RawGroupImpl(unsafe { _mm_set1_epi32(std::mem::transmute(value)) })
+        RawGroupImpl(unsafe { _mm_set1_epi32(value as i32) })
     }
 
     #[inline]
@@ -62,15 +84,7 @@ impl RawGroup for RawGroupImpl {
 
     #[inline]
     unsafe fn encode(output: *mut u8, group: Self) -> (u8, usize) {
-        // This implementation for generating the tag byte came from https://github.com/lemire/streamvbyte/blob/08c60644dc6956182c68c1b453ba5f2d42367823/src/streamvbytedelta_x64_encode.c
-        let mask_01 = _mm_set1_epi8(0x1);
-        let mask_7f00 = _mm_set1_epi16(0x7f00);
-
-        let a = _mm_min_epu8(mask_01, group.0);
-        let b = _mm_packus_epi16(a, a);
-        let c = _mm_min_epi16(b, mask_01);
-        let d = _mm_adds_epu16(c, mask_7f00);
-        let tag = _mm_movemask_epi8(d) as u8;
+        let tag = group.compute_tag();
 
         _mm_storeu_si128(
             output as *mut __m128i,
@@ -85,10 +99,7 @@ impl RawGroup for RawGroupImpl {
 
     #[inline]
     unsafe fn encode_deltas(output: *mut u8, base: Self, group: Self) -> (u8, usize) {
-        Self::encode(
-            output,
-            Self(_mm_sub_epi32(group.0, _mm_alignr_epi8(group.0, base.0, 12))),
-        )
+        Self::encode(output, Self::deltas(base, group))
     }
 
     #[inline]
@@ -119,6 +130,16 @@ impl RawGroup for RawGroupImpl {
         scalar::RawGroupImpl::data_len(tag)
     }
 
+    #[inline]
+    fn tag(group: Self) -> u8 {
+        unsafe { group.compute_tag() }
+    }
+
+    #[inline]
+    fn tag_deltas(base: Self, group: Self) -> u8 {
+        Self::tag(unsafe { Self::deltas(base, group) })
+    }
+
     #[inline]
     unsafe fn skip_deltas(input: *const u8, tag: u8) -> (usize, Self::Elem) {
         let (len, deltas) = Self::decode(input, tag);
@@ -152,6 +173,16 @@ impl RawGroup for RawGroupImpl {
         let sum64 = ((sum32 >> 32) & 0xffffffff) + (sum32 & 0xffffffff);
         sum64 as usize + 32
     }
+
+    #[inline]
+    fn find_ge(group: Self, target: u32) -> Option<usize> {
+        unsafe { find_ge32(group.0, target) }
+    }
+
+    #[inline]
+    fn match_any(a: Self, b: Self) -> u32 {
+        unsafe { match_any32(a.0, b.0) }
+    }
 }
 
 #[cfg(test)]
@@ -4,7 +4,7 @@ mod neon;
 mod sse41;
 
 use crate::coding_descriptor::CodingDescriptor;
-use crate::{coder_impl, Coder};
+use crate::{Coder, CoderId};
 
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct CodingDescriptor1248;
@@ -48,7 +48,7 @@ enum Impl {
     Scalar,
     #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
     Neon,
-    #[cfg(all(target_arch = "x86_64"))]
+    #[cfg(target_arch = "x86_64")]
     #[allow(dead_code)]
     SSE41,
 }
@@ -59,9 +59,13 @@ enum Impl {
 #[derive(Clone, Copy)]
 pub struct Coder1248(Impl);
 
+impl crate::private::Sealed for Coder1248 {}
+
 impl Coder for Coder1248 {
     type Elem = u64;
 
+    const ID: CoderId = CoderId::Coder1248;
+
     fn new() -> Self {
         #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
         {
@@ -82,89 +86,7 @@ impl Coder for Coder1248 {
         Coder1248(Impl::Scalar)
     }
 
-    fn encode(&self, values: &[u64], tags: &mut [u8], encoded: &mut [u8]) -> usize {
-        match self.0 {
-            Impl::Scalar => coder_impl::encode::<scalar::RawGroupImpl>(values, tags, encoded),
-            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
-            Impl::Neon => coder_impl::encode::<neon::RawGroupImpl>(values, tags, encoded),
-            #[cfg(target_arch = "x86_64")]
-            Impl::SSE41 => coder_impl::encode::<sse41::RawGroupImpl>(values, tags, encoded),
-        }
-    }
-
-    fn encode_deltas(
-        &self,
-        initial: u64,
-        values: &[u64],
-        tags: &mut [u8],
-        encoded: &mut [u8],
-    ) -> usize {
-        match self.0 {
-            Impl::Scalar => {
-                coder_impl::encode_deltas::<scalar::RawGroupImpl>(initial, values, tags, encoded)
-            }
-            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
-            Impl::Neon => {
-                coder_impl::encode_deltas::<neon::RawGroupImpl>(initial, values, tags, encoded)
-            }
-            #[cfg(target_arch = "x86_64")]
-            Impl::SSE41 => {
-                coder_impl::encode_deltas::<sse41::RawGroupImpl>(initial, values, tags, encoded)
-            }
-        }
-    }
-
-    fn decode(&self, tags: &[u8], encoded: &[u8], values: &mut [u64]) -> usize {
-        match self.0 {
-            Impl::Scalar => coder_impl::decode::<scalar::RawGroupImpl>(tags, encoded, values),
-            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
-            Impl::Neon => coder_impl::decode::<neon::RawGroupImpl>(tags, encoded, values),
-            #[cfg(target_arch = "x86_64")]
-            Impl::SSE41 => coder_impl::decode::<sse41::RawGroupImpl>(tags, encoded, values),
-        }
-    }
-
-    fn decode_deltas(
-        &self,
-        initial: u64,
-        tags: &[u8],
-        encoded: &[u8],
-        values: &mut [u64],
-    ) -> usize {
-        match self.0 {
-            Impl::Scalar => {
-                coder_impl::decode_deltas::<scalar::RawGroupImpl>(initial, tags, encoded, values)
-            }
-            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
-            Impl::Neon => {
-                coder_impl::decode_deltas::<neon::RawGroupImpl>(initial, tags, encoded, values)
-            }
-            #[cfg(target_arch = "x86_64")]
-            Impl::SSE41 => {
-                coder_impl::decode_deltas::<sse41::RawGroupImpl>(initial, tags, encoded, values)
-            }
-        }
-    }
-
-    fn data_len(&self, tags: &[u8]) -> usize {
-        match self.0 {
-            Impl::Scalar => coder_impl::data_len::<scalar::RawGroupImpl>(tags),
-            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
-            Impl::Neon => coder_impl::data_len::<neon::RawGroupImpl>(tags),
-            #[cfg(target_arch = "x86_64")]
-            Impl::SSE41 => coder_impl::data_len::<sse41::RawGroupImpl>(tags),
-        }
-    }
-
-    fn skip_deltas(&self, tags: &[u8], encoded: &[u8]) -> (usize, u64) {
-        match self.0 {
-            Impl::Scalar => coder_impl::skip_deltas::<scalar::RawGroupImpl>(tags, encoded),
-            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
-            Impl::Neon => coder_impl::skip_deltas::<neon::RawGroupImpl>(tags, encoded),
-            #[cfg(target_arch = "x86_64")]
-            Impl::SSE41 => coder_impl::skip_deltas::<sse41::RawGroupImpl>(tags, encoded),
-        }
-    }
+    crate::coder_impl::coder_methods!(u64, SSE41, sse41);
 }
 
 #[cfg(test)]
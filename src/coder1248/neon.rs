@@ -1,7 +1,9 @@
 use crunchy::unroll;
 
 use super::{scalar, CodingDescriptor1248};
-use crate::arch::neon::{data_len8, decode_shuffle_table, encode_shuffle_table};
+use crate::arch::neon::{
+    data_len8, decode_shuffle_table, encode_shuffle_table, find_ge64, match_any64,
+};
 use crate::coding_descriptor::CodingDescriptor;
 use crate::raw_group::RawGroup;
 use std::arch::aarch64::{
@@ -62,6 +64,14 @@ impl RawGroupImpl {
         (tag, written)
     }
 
+    /// Returns the difference between each element of group and the preceding element.
+    #[inline(always)]
+    unsafe fn deltas(base: Self, group: Self) -> Self {
+        let b0 = vextq_u64(base.1, group.0, 1);
+        let b1 = vextq_u64(group.0, group.1, 1);
+        RawGroupImpl(vsubq_u64(group.0, b0), vsubq_u64(group.1, b1))
+    }
+
     #[inline(always)]
     unsafe fn sum_deltas(&self, base: uint64x2_t) -> Self {
         let Self(a_b, c_d) = *self;
@@ -177,12 +187,7 @@ impl RawGroup for RawGroupImpl {
 
     #[inline]
     unsafe fn encode_deltas(output: *mut u8, base: Self, group: Self) -> (u8, usize) {
-        let b0 = vextq_u64(base.1, group.0, 1);
-        let b1 = vextq_u64(group.0, group.1, 1);
-        Self::encode(
-            output,
-            RawGroupImpl(vsubq_u64(group.0, b0), vsubq_u64(group.1, b1)),
-        )
+        Self::encode(output, Self::deltas(base, group))
     }
 
     #[inline]
@@ -210,6 +215,16 @@ impl RawGroup for RawGroupImpl {
         scalar::RawGroupImpl::data_len(tag)
     }
 
+    #[inline]
+    fn tag(group: Self) -> u8 {
+        unsafe { group.compute_tag().0 }
+    }
+
+    #[inline]
+    fn tag_deltas(base: Self, group: Self) -> u8 {
+        Self::tag(unsafe { Self::deltas(base, group) })
+    }
+
     #[inline]
     unsafe fn skip_deltas(input: *const u8, tag: u8) -> (usize, Self::Elem) {
         let (len, group) = Self::decode(input, tag);
@@ -276,6 +291,16 @@ impl RawGroup for RawGroupImpl {
         }
         (offset, sum)
     }
+
+    #[inline]
+    fn find_ge(group: Self, target: u64) -> Option<usize> {
+        unsafe { find_ge64(group.0, group.1, target) }
+    }
+
+    #[inline]
+    fn match_any(a: Self, b: Self) -> u32 {
+        unsafe { match_any64((a.0, a.1), (b.0, b.1)) }
+    }
 }
 
 #[cfg(test)]
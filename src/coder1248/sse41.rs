@@ -2,6 +2,7 @@ use crunchy::unroll;
 
 use super::{scalar, CodingDescriptor1248};
 use crate::arch::shuffle::{decode_shuffle_entry, encode_shuffle_entry};
+use crate::arch::x86::{find_ge64, match_any64};
 use crate::coding_descriptor::CodingDescriptor;
 use crate::raw_group::RawGroup;
 use std::arch::x86_64::{
@@ -74,6 +75,19 @@ impl RawGroupImpl {
         _mm_movemask_epi8(n2) as usize & 0xff
     }
 
+    /// Returns the difference between each element of group and the preceding element.
+    #[inline(always)]
+    unsafe fn deltas(base: Self, group: Self) -> Self {
+        let delta_base = (
+            _mm_alignr_epi8::<8>(group.0, base.1),
+            _mm_alignr_epi8::<8>(group.1, group.0),
+        );
+        RawGroupImpl(
+            _mm_sub_epi64(group.0, delta_base.0),
+            _mm_sub_epi64(group.1, delta_base.1),
+        )
+    }
+
     /// Splits input 8-bit `tag` into two nibble-length tags covering two entries instead of 4.
     #[inline(always)]
     fn nibble_tags(tag: usize) -> (usize, usize) {
@@ -95,7 +109,7 @@ impl RawGroup for RawGroupImpl {
     #[inline]
     fn set1(value: Self::Elem) -> Self {
         unsafe {
-            let h = _mm_set1_epi64x(std::mem::transmute(value));
+            let h = _mm_set1_epi64x(value as i64);
             RawGroupImpl(h, h)
         }
     }
@@ -130,15 +144,7 @@ impl RawGroup for RawGroupImpl {
 
     #[inline]
     unsafe fn encode_deltas(output: *mut u8, base: Self, group: Self) -> (u8, usize) {
-        let delta_base = (
-            _mm_alignr_epi8::<8>(group.0, base.1),
-            _mm_alignr_epi8::<8>(group.1, group.0),
-        );
-        let delta_group = RawGroupImpl(
-            _mm_sub_epi64(group.0, delta_base.0),
-            _mm_sub_epi64(group.1, delta_base.1),
-        );
-        Self::encode(output, delta_group)
+        Self::encode(output, Self::deltas(base, group))
     }
 
     #[inline]
@@ -176,12 +182,22 @@ impl RawGroup for RawGroupImpl {
         scalar::RawGroupImpl::data_len(tag)
     }
 
+    #[inline]
+    fn tag(group: Self) -> u8 {
+        unsafe { group.compute_tag() as u8 }
+    }
+
+    #[inline]
+    fn tag_deltas(base: Self, group: Self) -> u8 {
+        Self::tag(unsafe { Self::deltas(base, group) })
+    }
+
     #[inline]
     unsafe fn skip_deltas(input: *const u8, tag: u8) -> (usize, Self::Elem) {
         let (len, Self(a_b, c_d)) = Self::decode(input, tag);
         let ac_bd = _mm_add_epi64(a_b, c_d);
         let abcd_bd = _mm_add_epi64(_mm_bsrli_si128::<8>(ac_bd), ac_bd);
-        (len, std::mem::transmute(_mm_cvtsi128_si64x(abcd_bd)))
+        (len, _mm_cvtsi128_si64x(abcd_bd) as u64)
     }
 
     #[inline]
@@ -198,7 +214,17 @@ impl RawGroup for RawGroupImpl {
             }
         }
         sum_delta = _mm_add_epi64(sum_delta, _mm_bsrli_si128(sum_delta, 8));
-        (offset, std::mem::transmute(_mm_cvtsi128_si64x(sum_delta)))
+        (offset, _mm_cvtsi128_si64x(sum_delta) as u64)
+    }
+
+    #[inline]
+    fn find_ge(group: Self, target: u64) -> Option<usize> {
+        unsafe { find_ge64(group.0, group.1, target) }
+    }
+
+    #[inline]
+    fn match_any(a: Self, b: Self) -> u32 {
+        unsafe { match_any64((a.0, a.1), (b.0, b.1)) }
     }
 }
 
@@ -1,5 +1,6 @@
 use crate::raw_group::RawGroup;
 use num_traits::{ops::wrapping::WrappingAdd, Zero};
+use std::mem::MaybeUninit;
 
 trait EncodeSink<G>
 where
@@ -173,6 +174,21 @@ pub(crate) fn decode<G: RawGroup>(tags: &[u8], encoded: &[u8], values: &mut [G::
     )
 }
 
+#[inline]
+pub(crate) fn decode_uninit<G: RawGroup>(
+    tags: &[u8],
+    encoded: &[u8],
+    values: &mut [MaybeUninit<G::Elem>],
+) -> usize {
+    assert_eq!(values.len() % 4, 0);
+    assert_eq!(tags.len(), values.len() / 4);
+    decode_to_sink::<G, _>(
+        tags,
+        encoded,
+        &mut StandardDecodeSink::<G>(values.as_mut_ptr() as *mut G::Elem),
+    )
+}
+
 struct DeltaDecodeSink<G>(*mut G::Elem, G)
 where
     G: RawGroup;
@@ -213,6 +229,22 @@ pub(crate) fn decode_deltas<G: RawGroup>(
     )
 }
 
+#[inline]
+pub(crate) fn decode_deltas_uninit<G: RawGroup>(
+    initial: G::Elem,
+    tags: &[u8],
+    encoded: &[u8],
+    values: &mut [MaybeUninit<G::Elem>],
+) -> usize {
+    assert_eq!(values.len() % 4, 0);
+    assert_eq!(tags.len(), values.len() / 4);
+    decode_to_sink::<G, _>(
+        tags,
+        encoded,
+        &mut DeltaDecodeSink::<G>(values.as_mut_ptr() as *mut G::Elem, G::set1(initial)),
+    )
+}
+
 #[inline]
 pub(crate) fn data_len<G: RawGroup>(tags: &[u8]) -> usize {
     let mut len = 0usize;
@@ -227,6 +259,42 @@ pub(crate) fn data_len<G: RawGroup>(tags: &[u8]) -> usize {
     len
 }
 
+pub(crate) fn decode_value<G: RawGroup>(tag: u8, data: &[u8], index: usize) -> G::Elem {
+    assert!(index < 4);
+    let offset = (0..index)
+        .map(|i| G::TAG_LEN[((tag >> (i * 2)) & 0x3) as usize])
+        .sum::<usize>();
+    let len = G::TAG_LEN[((tag >> (index * 2)) & 0x3) as usize];
+    data[offset..(offset + len)]
+        .iter()
+        .rev()
+        .fold(G::Elem::zero(), |v, b| {
+            (v << 8) | <G::Elem as num_traits::NumCast>::from(*b).unwrap()
+        })
+}
+
+#[inline]
+pub(crate) fn compressed_len<G: RawGroup>(values: &[G::Elem]) -> usize {
+    assert_eq!(values.len() % 4, 0);
+    values
+        .chunks_exact(4)
+        .map(|g| G::data_len(G::tag(unsafe { G::load_unaligned(g.as_ptr()) })))
+        .sum()
+}
+
+#[inline]
+pub(crate) fn compressed_len_deltas<G: RawGroup>(initial: G::Elem, values: &[G::Elem]) -> usize {
+    assert_eq!(values.len() % 4, 0);
+    let mut base = G::set1(initial);
+    let mut len = 0;
+    for g in values.chunks_exact(4) {
+        let group = unsafe { G::load_unaligned(g.as_ptr()) };
+        len += G::data_len(G::tag_deltas(base, group));
+        base = group;
+    }
+    len
+}
+
 struct SkipDeltasSink<G: RawGroup>(G::Elem);
 
 impl<G> DecodeSink for SkipDeltasSink<G>
@@ -258,3 +326,392 @@ where
     let read = decode_to_sink::<G, _>(tags, data, &mut sink);
     (read, sink.0)
 }
+
+/// Forwards to another sink with all tag indices offset by a fixed amount.
+struct OffsetDecodeSink<'a, S: DecodeSink>(&'a mut S, usize);
+
+impl<S: DecodeSink> DecodeSink for OffsetDecodeSink<'_, S> {
+    #[inline]
+    unsafe fn handle1(&mut self, tag_index: usize, tag: u8, data: *const u8) -> usize {
+        self.0.handle1(self.1 + tag_index, tag, data)
+    }
+
+    #[inline]
+    unsafe fn handle8(&mut self, tag_index: usize, tag8: u64, data: *const u8) -> usize {
+        self.0.handle8(self.1 + tag_index, tag8, data)
+    }
+}
+
+/// Returns the number of leading groups in `tags` whose data fits in `len` bytes.
+#[inline]
+fn groups_within<G: RawGroup>(tags: &[u8], len: usize) -> usize {
+    let mut total = 0usize;
+    let mut tag_index = 0;
+    for tag_chunk in tags.chunks_exact(8) {
+        let tag8 = unsafe { std::ptr::read_unaligned(tag_chunk.as_ptr() as *const u64) };
+        let l = G::data_len8(tag8);
+        if total + l > len {
+            break;
+        }
+        total += l;
+        tag_index += 8;
+    }
+    for tag in &tags[tag_index..] {
+        total += G::data_len(*tag);
+        if total > len {
+            break;
+        }
+        tag_index += 1;
+    }
+    tag_index
+}
+
+/// Decode tags and data that are each split across several segments.
+///
+/// Runs of groups that are contiguous within a data segment are passed to `decode_to_sink()` so
+/// that they may use the 8 group fast path; only groups that straddle a data segment boundary
+/// are stitched together in a scratch buffer.
+#[inline]
+fn decode_segments_to_sink<G: RawGroup, S: DecodeSink>(
+    tags: &[&[u8]],
+    data: &[&[u8]],
+    sink: &mut S,
+) -> usize {
+    let mut data_segments = data.iter();
+    let mut segment: &[u8] = &[];
+    let mut read = 0usize;
+    let mut tag_index = 0usize;
+    for tag_segment in tags {
+        let mut tags = *tag_segment;
+        while !tags.is_empty() {
+            let n = groups_within::<G>(tags, segment.len());
+            if n > 0 {
+                let r = decode_to_sink::<G, _>(
+                    &tags[..n],
+                    segment,
+                    &mut OffsetDecodeSink(sink, tag_index),
+                );
+                segment = &segment[r..];
+                read += r;
+                tag_index += n;
+                tags = &tags[n..];
+                continue;
+            }
+
+            if segment.is_empty() {
+                segment = data_segments
+                    .next()
+                    .expect("data segments are shorter than tags");
+                continue;
+            }
+
+            // The next group straddles a segment boundary. Copy it to a scratch buffer that is
+            // large enough for a group of 8 byte elements plus any slop read by G.
+            let len = G::data_len(tags[0]);
+            let mut buf = [0u8; 64];
+            let mut filled = 0;
+            while filled < len {
+                if segment.is_empty() {
+                    segment = data_segments
+                        .next()
+                        .expect("data segments are shorter than tags");
+                }
+                let k = std::cmp::min(len - filled, segment.len());
+                buf[filled..(filled + k)].copy_from_slice(&segment[..k]);
+                segment = &segment[k..];
+                filled += k;
+            }
+            let r = unsafe { sink.handle1(tag_index, tags[0], buf.as_ptr()) };
+            debug_assert_eq!(r, len);
+            read += r;
+            tag_index += 1;
+            tags = &tags[1..];
+        }
+    }
+    read
+}
+
+#[inline]
+pub(crate) fn decode_segments<G: RawGroup>(
+    tags: &[&[u8]],
+    encoded: &[&[u8]],
+    values: &mut [G::Elem],
+) -> usize {
+    assert_eq!(values.len() % 4, 0);
+    assert_eq!(
+        tags.iter().map(|t| t.len()).sum::<usize>(),
+        values.len() / 4
+    );
+    decode_segments_to_sink::<G, _>(
+        tags,
+        encoded,
+        &mut StandardDecodeSink::<G>(values.as_mut_ptr()),
+    )
+}
+
+#[inline]
+pub(crate) fn decode_deltas_segments<G: RawGroup>(
+    initial: G::Elem,
+    tags: &[&[u8]],
+    encoded: &[&[u8]],
+    values: &mut [G::Elem],
+) -> usize {
+    assert_eq!(values.len() % 4, 0);
+    assert_eq!(
+        tags.iter().map(|t| t.len()).sum::<usize>(),
+        values.len() / 4
+    );
+    decode_segments_to_sink::<G, _>(
+        tags,
+        encoded,
+        &mut DeltaDecodeSink::<G>(values.as_mut_ptr(), G::set1(initial)),
+    )
+}
+
+#[inline]
+pub(crate) fn skip_deltas_segments<G: RawGroup>(
+    tags: &[&[u8]],
+    encoded: &[&[u8]],
+) -> (usize, G::Elem)
+where
+    <G as RawGroup>::Elem: WrappingAdd,
+{
+    let mut sink = SkipDeltasSink::<G>(G::Elem::zero());
+    let read = decode_segments_to_sink::<G, _>(tags, encoded, &mut sink);
+    (read, sink.0)
+}
+
+/// Decode the single group of deltas with `tag` at the start of `data` from `base`.
+///
+/// Unlike `RawGroup::decode_deltas()` this is safe to call near the end of `data`.
+#[inline]
+fn decode_group_deltas<G: RawGroup>(tag: u8, data: &[u8], base: G::Elem) -> (usize, G) {
+    if G::TAG_LEN[3] * 4 <= data.len() {
+        unsafe { G::decode_deltas(data.as_ptr(), tag, G::set1(base)) }
+    } else {
+        // Copy the tail of data to a scratch buffer, as in decode_to_sink().
+        let mut buf = [0u8; 64];
+        buf[..data.len()].copy_from_slice(data);
+        let r = unsafe { G::decode_deltas(buf.as_ptr(), tag, G::set1(base)) };
+        assert!(r.0 <= data.len());
+        r
+    }
+}
+
+/// Find the first value greater than or equal to `target` in a sorted delta coded stream.
+///
+/// Skips chunks of 8 groups using `skip_deltas8()` while the running sum at the end of the chunk
+/// is less than target, then decodes individual groups until the value is found.
+#[inline]
+pub(crate) fn find_ge<G: RawGroup>(
+    initial: G::Elem,
+    tags: &[u8],
+    data: &[u8],
+    target: G::Elem,
+) -> Option<(usize, G::Elem)>
+where
+    <G as RawGroup>::Elem: WrappingAdd,
+{
+    let mut base = initial;
+    let mut read = 0usize;
+    let mut tag_index = 0;
+    for tag_chunk in tags.chunks_exact(8) {
+        let tag8 = unsafe { std::ptr::read_unaligned(tag_chunk.as_ptr() as *const u64) };
+        // See decode_to_sink() for an explanation of max_read.
+        let max_read = G::data_len8(tag8) + (G::TAG_LEN[3] - G::TAG_LEN[0]) * 4;
+        if read + max_read > data.len() {
+            break;
+        }
+        let (r, sum) = unsafe { G::skip_deltas8(data.as_ptr().add(read), tag8) };
+        let last = base.wrapping_add(&sum);
+        if last >= target {
+            break;
+        }
+        base = last;
+        read += r;
+        tag_index += 8;
+    }
+
+    for tag in &tags[tag_index..] {
+        let (r, group) = decode_group_deltas::<G>(*tag, &data[read..], base);
+        let mut values = [G::Elem::zero(); 4];
+        unsafe { G::store_unaligned(values.as_mut_ptr(), group) };
+        if let Some(i) = G::find_ge(group, target) {
+            return Some((tag_index * 4 + i, values[i]));
+        }
+        base = values[3];
+        read += r;
+        tag_index += 1;
+    }
+    None
+}
+
+/// Append every value in sorted `values` that also appears in a sorted delta coded stream to `out`.
+///
+/// Runs of groups that cannot contain the next value are skipped by galloping with
+/// `skip_deltas()`, starting from a step sized by the distance covered for the previous value.
+/// Single groups are decoded rather than skipped so that no group is both skipped and decoded, and
+/// each decoded group is compared to `values` with `RawGroup::match_any()`.
+pub(crate) fn intersect_deltas<G: RawGroup>(
+    initial: G::Elem,
+    tags: &[u8],
+    data: &[u8],
+    values: &[G::Elem],
+    out: &mut Vec<G::Elem>,
+) where
+    <G as RawGroup>::Elem: WrappingAdd,
+{
+    let mut base = initial;
+    let mut read = 0usize;
+    let mut tag_index = 0;
+    let mut i = 0;
+    // Number of groups advanced to reach the previous candidate group.
+    let mut stride = 1;
+    while i < values.len() && tag_index < tags.len() {
+        // Gallop past groups whose last value is less than the next value.
+        let target = values[i];
+        let start = tag_index;
+        let mut step = std::cmp::max(stride / 2, 1);
+        let mut growing = true;
+        let mut candidate = None;
+        while tag_index < tags.len() {
+            if step == 1 {
+                let (r, group) = decode_group_deltas::<G>(tags[tag_index], &data[read..], base);
+                let mut group_values = [G::Elem::zero(); 4];
+                unsafe { G::store_unaligned(group_values.as_mut_ptr(), group) };
+                if group_values[3] >= target {
+                    candidate = Some((r, group, group_values[3]));
+                    break;
+                }
+                base = group_values[3];
+                read += r;
+                tag_index += 1;
+                if growing {
+                    step = 2;
+                }
+                continue;
+            }
+            let end = std::cmp::min(tag_index + step, tags.len());
+            let (r, sum) = skip_deltas::<G>(&tags[tag_index..end], &data[read..]);
+            if base.wrapping_add(&sum) < target {
+                base = base.wrapping_add(&sum);
+                read += r;
+                tag_index = end;
+                if growing {
+                    step *= 2;
+                }
+            } else {
+                growing = false;
+                step /= 2;
+            }
+        }
+        let (r, group, last) = match candidate {
+            Some(c) => c,
+            None => break,
+        };
+        stride = tag_index + 1 - start;
+
+        // Every remaining value <= last may only appear in this group.
+        while i < values.len() && values[i] <= last {
+            let n = values[i..]
+                .iter()
+                .take(4)
+                .take_while(|v| **v <= last)
+                .count();
+            let mut candidates = [values[i + n - 1]; 4];
+            candidates[..n].copy_from_slice(&values[i..(i + n)]);
+            let mask = G::match_any(unsafe { G::load_unaligned(candidates.as_ptr()) }, group)
+                & ((1 << n) - 1);
+            out.extend(
+                (0..n)
+                    .filter(|j| mask & (1 << j) != 0)
+                    .map(|j| candidates[j]),
+            );
+            i += n;
+        }
+        base = last;
+        read += r;
+        tag_index += 1;
+    }
+}
+
+/// Implement the required `Coder` methods for a coder whose `Impl` enum selects between the scalar,
+/// `NEON` and `$x86` raw group implementations, dispatching each call to the function of the same
+/// name in this module.
+macro_rules! coder_methods {
+    ($elem:ty, $x86:ident, $x86_mod:ident) => {
+        fn encode(&self, values: &[$elem], tags: &mut [u8], encoded: &mut [u8]) -> usize {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, encode(values, tags, encoded))
+        }
+
+        fn encode_deltas(&self, initial: $elem, values: &[$elem], tags: &mut [u8], encoded: &mut [u8]) -> usize {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, encode_deltas(initial, values, tags, encoded))
+        }
+
+        fn compressed_len(&self, values: &[$elem]) -> usize {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, compressed_len(values))
+        }
+
+        fn compressed_len_deltas(&self, initial: $elem, values: &[$elem]) -> usize {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, compressed_len_deltas(initial, values))
+        }
+
+        fn decode(&self, tags: &[u8], encoded: &[u8], values: &mut [$elem]) -> usize {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, decode(tags, encoded, values))
+        }
+
+        fn decode_deltas(&self, initial: $elem, tags: &[u8], encoded: &[u8], values: &mut [$elem]) -> usize {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, decode_deltas(initial, tags, encoded, values))
+        }
+
+        fn decode_uninit(&self, tags: &[u8], encoded: &[u8], values: &mut [std::mem::MaybeUninit<$elem>]) -> usize {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, decode_uninit(tags, encoded, values))
+        }
+
+        fn decode_deltas_uninit(&self, initial: $elem, tags: &[u8], encoded: &[u8], values: &mut [std::mem::MaybeUninit<$elem>]) -> usize {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, decode_deltas_uninit(initial, tags, encoded, values))
+        }
+
+        fn decode_segments(&self, tags: &[&[u8]], encoded: &[&[u8]], values: &mut [$elem]) -> usize {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, decode_segments(tags, encoded, values))
+        }
+
+        fn decode_deltas_segments(&self, initial: $elem, tags: &[&[u8]], encoded: &[&[u8]], values: &mut [$elem]) -> usize {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, decode_deltas_segments(initial, tags, encoded, values))
+        }
+
+        fn data_len(&self, tags: &[u8]) -> usize {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, data_len(tags))
+        }
+
+        fn decode_value(&self, tag: u8, data: &[u8], index: usize) -> $elem {
+            $crate::coder_impl::decode_value::<scalar::RawGroupImpl>(tag, data, index)
+        }
+
+        fn skip_deltas(&self, tags: &[u8], encoded: &[u8]) -> (usize, $elem) {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, skip_deltas(tags, encoded))
+        }
+
+        fn find_ge(&self, initial: $elem, tags: &[u8], encoded: &[u8], target: $elem) -> Option<(usize, $elem)> {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, find_ge(initial, tags, encoded, target))
+        }
+
+        fn intersect_deltas(&self, initial: $elem, tags: &[u8], encoded: &[u8], values: &[$elem], out: &mut Vec<$elem>) {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, intersect_deltas(initial, tags, encoded, values, out))
+        }
+
+        fn skip_deltas_segments(&self, tags: &[&[u8]], encoded: &[&[u8]]) -> (usize, $elem) {
+            $crate::coder_impl::coder_methods!(@dispatch self, $x86, $x86_mod, skip_deltas_segments(tags, encoded))
+        }
+    };
+    (@dispatch $self:ident, $x86:ident, $x86_mod:ident, $f:ident($($arg:expr),*)) => {
+        match $self.0 {
+            Impl::Scalar => $crate::coder_impl::$f::<scalar::RawGroupImpl>($($arg),*),
+            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+            Impl::Neon => $crate::coder_impl::$f::<neon::RawGroupImpl>($($arg),*),
+            #[cfg(target_arch = "x86_64")]
+            Impl::$x86 => $crate::coder_impl::$f::<$x86_mod::RawGroupImpl>($($arg),*),
+        }
+    };
+}
+pub(crate) use coder_methods;
@@ -0,0 +1,321 @@
+//! Coders selected at runtime from a `CoderId`.
+
+use crate::{Coder, Coder0124, Coder1234, Coder1248, CoderId, Error};
+
+/// Define an enum over the coders for one element type that forwards `Coder` methods to the
+/// selected coder, dispatching once per call.
+macro_rules! dyn_coder {
+    ($(#[$meta:meta])* $name:ident, $elem:ty, [$($coder:ident),+]) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy)]
+        pub enum $name {
+            $(
+                #[doc = concat!("Values are coded with `", stringify!($coder), "`.")]
+                $coder($coder),
+            )+
+        }
+
+        impl $name {
+            /// Create the coder identified by `id`, selecting the fastest implementation available.
+            ///
+            /// Returns `Error::ElemMismatch` if the coder identified by `id` does not code this
+            /// element type.
+            pub fn new(id: CoderId) -> Result<Self, Error> {
+                match id {
+                    $(CoderId::$coder => Ok(Self::$coder($coder::new())),)+
+                    _ => Err(Error::ElemMismatch(id)),
+                }
+            }
+
+            /// Returns the identifier of the selected coder.
+            pub fn id(&self) -> CoderId {
+                match self {
+                    $(Self::$coder(_) => CoderId::$coder,)+
+                }
+            }
+
+            /// See `Coder::max_compressed_bytes()`.
+            pub fn max_compressed_bytes(&self, len: usize) -> (usize, usize) {
+                match self {
+                    $(Self::$coder(_) => $coder::max_compressed_bytes(len),)+
+                }
+            }
+
+            /// See `Coder::encode()`.
+            pub fn encode(&self, values: &[$elem], tags: &mut [u8], data: &mut [u8]) -> usize {
+                match self {
+                    $(Self::$coder(c) => c.encode(values, tags, data),)+
+                }
+            }
+
+            /// See `Coder::encode_deltas()`.
+            pub fn encode_deltas(
+                &self,
+                initial: $elem,
+                values: &[$elem],
+                tags: &mut [u8],
+                data: &mut [u8],
+            ) -> usize {
+                match self {
+                    $(Self::$coder(c) => c.encode_deltas(initial, values, tags, data),)+
+                }
+            }
+
+            /// See `Coder::encode_into_vec()`.
+            pub fn encode_into_vec(
+                &self,
+                values: &[$elem],
+                tags: &mut Vec<u8>,
+                data: &mut Vec<u8>,
+            ) -> usize {
+                match self {
+                    $(Self::$coder(c) => c.encode_into_vec(values, tags, data),)+
+                }
+            }
+
+            /// See `Coder::encode_deltas_into_vec()`.
+            pub fn encode_deltas_into_vec(
+                &self,
+                initial: $elem,
+                values: &[$elem],
+                tags: &mut Vec<u8>,
+                data: &mut Vec<u8>,
+            ) -> usize {
+                match self {
+                    $(Self::$coder(c) => c.encode_deltas_into_vec(initial, values, tags, data),)+
+                }
+            }
+
+            /// See `Coder::compressed_len()`.
+            pub fn compressed_len(&self, values: &[$elem]) -> usize {
+                match self {
+                    $(Self::$coder(c) => c.compressed_len(values),)+
+                }
+            }
+
+            /// See `Coder::compressed_len_deltas()`.
+            pub fn compressed_len_deltas(&self, initial: $elem, values: &[$elem]) -> usize {
+                match self {
+                    $(Self::$coder(c) => c.compressed_len_deltas(initial, values),)+
+                }
+            }
+
+            /// See `Coder::decode()`.
+            pub fn decode(&self, tags: &[u8], data: &[u8], values: &mut [$elem]) -> usize {
+                match self {
+                    $(Self::$coder(c) => c.decode(tags, data, values),)+
+                }
+            }
+
+            /// See `Coder::decode_deltas()`.
+            pub fn decode_deltas(
+                &self,
+                initial: $elem,
+                tags: &[u8],
+                data: &[u8],
+                values: &mut [$elem],
+            ) -> usize {
+                match self {
+                    $(Self::$coder(c) => c.decode_deltas(initial, tags, data, values),)+
+                }
+            }
+
+            /// See `Coder::decode_to_vec()`.
+            pub fn decode_to_vec(&self, tags: &[u8], data: &[u8], values: &mut Vec<$elem>) -> usize {
+                match self {
+                    $(Self::$coder(c) => c.decode_to_vec(tags, data, values),)+
+                }
+            }
+
+            /// See `Coder::decode_deltas_to_vec()`.
+            pub fn decode_deltas_to_vec(
+                &self,
+                initial: $elem,
+                tags: &[u8],
+                data: &[u8],
+                values: &mut Vec<$elem>,
+            ) -> usize {
+                match self {
+                    $(Self::$coder(c) => c.decode_deltas_to_vec(initial, tags, data, values),)+
+                }
+            }
+
+            /// See `Coder::decode_range()`.
+            pub fn decode_range(
+                &self,
+                tags: &[u8],
+                data: &[u8],
+                range: std::ops::Range<usize>,
+                values: &mut [$elem],
+            ) {
+                match self {
+                    $(Self::$coder(c) => c.decode_range(tags, data, range, values),)+
+                }
+            }
+
+            /// See `Coder::decode_deltas_range()`.
+            pub fn decode_deltas_range(
+                &self,
+                initial: $elem,
+                tags: &[u8],
+                data: &[u8],
+                range: std::ops::Range<usize>,
+                values: &mut [$elem],
+            ) {
+                match self {
+                    $(Self::$coder(c) => c.decode_deltas_range(initial, tags, data, range, values),)+
+                }
+            }
+
+            /// See `Coder::data_len()`.
+            pub fn data_len(&self, tags: &[u8]) -> usize {
+                match self {
+                    $(Self::$coder(c) => c.data_len(tags),)+
+                }
+            }
+
+            /// See `Coder::skip_deltas()`.
+            pub fn skip_deltas(&self, tags: &[u8], data: &[u8]) -> (usize, $elem) {
+                match self {
+                    $(Self::$coder(c) => c.skip_deltas(tags, data),)+
+                }
+            }
+
+            /// See `Coder::find_ge()`.
+            pub fn find_ge(
+                &self,
+                initial: $elem,
+                tags: &[u8],
+                data: &[u8],
+                target: $elem,
+            ) -> Option<(usize, $elem)> {
+                match self {
+                    $(Self::$coder(c) => c.find_ge(initial, tags, data, target),)+
+                }
+            }
+        }
+
+        impl TryFrom<CoderId> for $name {
+            type Error = Error;
+
+            fn try_from(id: CoderId) -> Result<Self, Self::Error> {
+                Self::new(id)
+            }
+        }
+
+        impl TryFrom<u8> for $name {
+            type Error = Error;
+
+            fn try_from(id: u8) -> Result<Self, Self::Error> {
+                Self::new(CoderId::try_from(id)?)
+            }
+        }
+    };
+}
+
+dyn_coder!(
+    /// A coder for `u32` values chosen at runtime, for example from file metadata.
+    ///
+    /// The `Coder` trait cannot be used as a trait object, so code that reads the format from its
+    /// input would otherwise be monomorphized for each coder. `DynCoder32` wraps `Coder0124` or
+    /// `Coder1234` and forwards each method to the selected coder with a single dispatch per call.
+    ///
+    /// ```
+    /// use streamvbyte64::{CoderId, DynCoder32};
+    ///
+    /// let coder = DynCoder32::try_from(CoderId::Coder0124 as u8).unwrap();
+    /// let values = [0u32, 7, 0, 300, 1, 2, 3, 4];
+    /// let (tag_len, data_len) = coder.max_compressed_bytes(values.len());
+    /// let mut tags = vec![0u8; tag_len];
+    /// let mut data = vec![0u8; data_len];
+    /// let data_len = coder.encode(&values, &mut tags, &mut data);
+    ///
+    /// let mut decoded = [0u32; 8];
+    /// coder.decode(&tags, &data[..data_len], &mut decoded);
+    /// assert_eq!(values, decoded);
+    /// assert_eq!(CoderId::Coder0124, coder.id());
+    /// ```
+    DynCoder32,
+    u32,
+    [Coder0124, Coder1234]
+);
+
+dyn_coder!(
+    /// A coder for `u64` values chosen at runtime.
+    ///
+    /// See `DynCoder32`; `Coder1248` is currently the only 64-bit coder.
+    DynCoder64,
+    u64,
+    [Coder1248]
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::generate_cumulative_array;
+
+    #[test]
+    fn matches_static_coder() {
+        for id in [CoderId::Coder0124, CoderId::Coder1234] {
+            let coder = DynCoder32::new(id).unwrap();
+            assert_eq!(id, coder.id());
+            let values = generate_cumulative_array::<u32>(1024, 2, 3);
+            let mut tags = vec![];
+            let mut data = vec![];
+            let data_len = coder.encode_deltas_into_vec(3, &values, &mut tags, &mut data);
+            assert_eq!(data_len, coder.data_len(&tags));
+            assert_eq!(data_len, coder.compressed_len_deltas(3, &values));
+            assert_eq!(
+                (data_len, values[1023].wrapping_sub(3)),
+                coder.skip_deltas(&tags, &data)
+            );
+            let mut decoded = vec![];
+            coder.decode_deltas_to_vec(3, &tags, &data, &mut decoded);
+            assert_eq!(values, decoded);
+
+            let (expected_tags, expected_data) = match id {
+                CoderId::Coder0124 => encode_with(Coder0124::new(), &values),
+                _ => encode_with(Coder1234::new(), &values),
+            };
+            assert_eq!(expected_tags, tags);
+            assert_eq!(expected_data, data);
+        }
+
+        let coder = DynCoder64::new(CoderId::Coder1248).unwrap();
+        let values = generate_cumulative_array::<u64>(1024, 8, 0);
+        let (tag_len, data_len) = coder.max_compressed_bytes(values.len());
+        let mut tags = vec![0u8; tag_len];
+        let mut data = vec![0u8; data_len];
+        let data_len = coder.encode(&values, &mut tags, &mut data);
+        let mut decoded = vec![0u64; values.len()];
+        assert_eq!(
+            data_len,
+            coder.decode(&tags, &data[..data_len], &mut decoded)
+        );
+        assert_eq!(values, decoded);
+    }
+
+    fn encode_with<C: Coder<Elem = u32>>(coder: C, values: &[u32]) -> (Vec<u8>, Vec<u8>) {
+        let mut tags = vec![];
+        let mut data = vec![];
+        coder.encode_deltas_into_vec(3, values, &mut tags, &mut data);
+        (tags, data)
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Some(Error::ElemMismatch(CoderId::Coder1248)),
+            DynCoder32::new(CoderId::Coder1248).err()
+        );
+        assert_eq!(
+            Some(Error::ElemMismatch(CoderId::Coder1234)),
+            DynCoder64::try_from(CoderId::Coder1234).err()
+        );
+        assert_eq!(
+            Some(Error::UnknownCoder(0)),
+            DynCoder32::try_from(0u8).err()
+        );
+        assert_eq!(CoderId::Coder1248, DynCoder64::try_from(3u8).unwrap().id());
+    }
+}
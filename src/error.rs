@@ -0,0 +1,77 @@
+use crate::CoderId;
+
+/// Errors produced when parsing or decoding self-describing encoded input.
+///
+/// The raw `Coder` methods trust their input and panic on violated preconditions; formats that
+/// carry their own metadata validate it and report problems with this type instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The input ended before all of the bytes described by its header.
+    Truncated,
+    /// The input was written by an unsupported format version.
+    UnsupportedVersion(u8),
+    /// The input names a coder that is not known to this library.
+    UnknownCoder(u8),
+    /// The input was encoded by a coder whose element type differs from the requested type.
+    ElemMismatch(CoderId),
+    /// The input was encoded by a different coder than the one used to decode it.
+    CoderMismatch {
+        /// Coder used to decode the input.
+        expected: CoderId,
+        /// Coder recorded in the input.
+        actual: CoderId,
+    },
+    /// A header field is inconsistent with the rest of the input.
+    InvalidHeader(&'static str),
+    /// The tags describe a different amount of data than the input contains.
+    DataLenMismatch {
+        /// Data length computed from the tag stream.
+        expected: usize,
+        /// Data length recorded in the input.
+        actual: usize,
+    },
+    /// The checksum computed over the input does not match the recorded checksum.
+    ChecksumMismatch {
+        /// Checksum recorded in the input.
+        expected: u32,
+        /// Checksum computed from the input.
+        actual: u32,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Truncated => write!(f, "input is truncated"),
+            Error::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            Error::UnknownCoder(id) => write!(f, "unknown coder id {}", id),
+            Error::ElemMismatch(id) => write!(f, "{:?} does not produce the requested type", id),
+            Error::CoderMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "expected input from {:?} but found {:?}",
+                    expected, actual
+                )
+            }
+            Error::InvalidHeader(field) => write!(f, "invalid header field {}", field),
+            Error::DataLenMismatch { expected, actual } => write!(
+                f,
+                "tags describe {} data bytes but input contains {}",
+                expected, actual
+            ),
+            Error::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "checksum mismatch: expected {:#010x} computed {:#010x}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for std::io::Error {
+    fn from(e: Error) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+    }
+}
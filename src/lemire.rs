@@ -0,0 +1,331 @@
+//! Byte-compatible implementation of the single buffer format used by Daniel Lemire's C
+//! [`streamvbyte`](https://github.com/lemire/streamvbyte) library.
+//!
+//! The C library writes control (tag) bytes and data into a single buffer: `(count + 3) / 4`
+//! control bytes followed immediately by the data bytes. Unlike `Coder` the number of values does
+//! not need to be a multiple of 4; the final control byte only describes the values that are
+//! present and the data stream contains nothing for the missing values. The caller is responsible
+//! for recording `count`.
+//!
+//! Whole groups are coded using the accelerated `Coder1234` and `Coder0124` implementations, only
+//! the trailing partial group is coded one value at a time.
+//!
+//! | C function                   | Rust function       |
+//! | ---------------------------- | ------------------- |
+//! | `streamvbyte_encode`         | [`encode`]          |
+//! | `streamvbyte_decode`         | [`decode`]          |
+//! | `streamvbyte_encode_0124`    | [`encode_0124`]     |
+//! | `streamvbyte_decode_0124`    | [`decode_0124`]     |
+//! | `streamvbyte_delta_encode`   | [`encode_deltas`]   |
+//! | `streamvbyte_delta_decode`   | [`decode_deltas`]   |
+//!
+//! ```
+//! use streamvbyte64::lemire;
+//!
+//! let values = [1u32, 2, 300, 70000, 5];
+//! let mut encoded = vec![0u8; lemire::max_compressed_bytes(values.len())];
+//! let len = lemire::encode(&values, &mut encoded);
+//! assert_eq!(&encoded[..len], &[0x90, 0x00, 1, 2, 0x2c, 0x01, 0x70, 0x11, 0x01, 5]);
+//!
+//! let mut decoded = [0u32; 5];
+//! assert_eq!(len, lemire::decode(&encoded[..len], &mut decoded));
+//! assert_eq!(values, decoded);
+//! ```
+
+use crate::coder0124::CodingDescriptor0124;
+use crate::coder1234::CodingDescriptor1234;
+use crate::coding_descriptor::CodingDescriptor;
+use crate::{Coder, Coder0124, Coder1234};
+
+/// Returns the maximum number of bytes required to encode `len` values in any of the formats in
+/// this module.
+///
+/// This is equivalent to `streamvbyte_max_compressedbytes()` without the trailing padding that
+/// newer versions of the C library require for decoding.
+pub fn max_compressed_bytes(len: usize) -> usize {
+    len.div_ceil(4) + len * std::mem::size_of::<u32>()
+}
+
+/// Encodes `values` to `output` in the same format as `streamvbyte_encode()`.
+///
+/// Returns the total number of bytes written to `output`.
+///
+/// # Panics
+///
+/// - If `output.len() < max_compressed_bytes(values.len())`.
+pub fn encode(values: &[u32], output: &mut [u8]) -> usize {
+    encode_with::<_, CodingDescriptor1234>(Coder1234::new(), None, values, output)
+}
+
+/// Decodes `values.len()` values from `input` in the same format as `streamvbyte_decode()`.
+///
+/// Returns the total number of bytes read from `input`.
+///
+/// # Panics
+///
+/// - If decoding would consume bytes past the end of `input`.
+pub fn decode(input: &[u8], values: &mut [u32]) -> usize {
+    decode_with::<_, CodingDescriptor1234>(Coder1234::new(), None, input, values)
+}
+
+/// Encodes `values` to `output` in the same format as `streamvbyte_encode_0124()`.
+///
+/// Returns the total number of bytes written to `output`.
+///
+/// # Panics
+///
+/// - If `output.len() < max_compressed_bytes(values.len())`.
+pub fn encode_0124(values: &[u32], output: &mut [u8]) -> usize {
+    encode_with::<_, CodingDescriptor0124>(Coder0124::new(), None, values, output)
+}
+
+/// Decodes `values.len()` values from `input` in the same format as `streamvbyte_decode_0124()`.
+///
+/// Returns the total number of bytes read from `input`.
+///
+/// # Panics
+///
+/// - If decoding would consume bytes past the end of `input`.
+pub fn decode_0124(input: &[u8], values: &mut [u32]) -> usize {
+    decode_with::<_, CodingDescriptor0124>(Coder0124::new(), None, input, values)
+}
+
+/// Encodes the differences between successive `values` starting from `prev` to `output` in the
+/// same format as `streamvbyte_delta_encode()`.
+///
+/// Returns the total number of bytes written to `output`.
+///
+/// # Panics
+///
+/// - If `output.len() < max_compressed_bytes(values.len())`.
+pub fn encode_deltas(prev: u32, values: &[u32], output: &mut [u8]) -> usize {
+    encode_with::<_, CodingDescriptor1234>(Coder1234::new(), Some(prev), values, output)
+}
+
+/// Decodes `values.len()` deltas from `input` starting from `prev` in the same format as
+/// `streamvbyte_delta_decode()`.
+///
+/// Returns the total number of bytes read from `input`.
+///
+/// # Panics
+///
+/// - If decoding would consume bytes past the end of `input`.
+pub fn decode_deltas(prev: u32, input: &[u8], values: &mut [u32]) -> usize {
+    decode_with::<_, CodingDescriptor1234>(Coder1234::new(), Some(prev), input, values)
+}
+
+/// Encode `values` with `C` and code any trailing partial group using `D`.
+/// If `prev` is set values are delta coded.
+fn encode_with<C, D>(coder: C, prev: Option<u32>, values: &[u32], output: &mut [u8]) -> usize
+where
+    C: Coder<Elem = u32>,
+    D: CodingDescriptor<Elem = u32>,
+{
+    assert!(output.len() >= max_compressed_bytes(values.len()));
+    let (tags, data) = output.split_at_mut(values.len().div_ceil(4));
+    let (groups, remainder) = values.split_at(values.len() & !3);
+    let mut written = match prev {
+        Some(p) => coder.encode_deltas(p, groups, &mut tags[..groups.len() / 4], data),
+        None => coder.encode(groups, &mut tags[..groups.len() / 4], data),
+    };
+
+    if !remainder.is_empty() {
+        let mut base = prev.map(|p| groups.last().copied().unwrap_or(p));
+        let mut tag = 0u8;
+        for (i, v) in remainder.iter().enumerate() {
+            let value = match base {
+                Some(b) => {
+                    base = Some(*v);
+                    v.wrapping_sub(b)
+                }
+                None => *v,
+            };
+            let (vtag, len) = D::tag_value(value);
+            data[written..(written + len)].copy_from_slice(&value.to_le_bytes()[..len]);
+            tag |= vtag << (i * 2);
+            written += len;
+        }
+        tags[tags.len() - 1] = tag;
+    }
+
+    tags.len() + written
+}
+
+/// Decode `values` with `C` and decode any trailing partial group using `D`.
+/// If `prev` is set values are delta coded.
+fn decode_with<C, D>(coder: C, prev: Option<u32>, input: &[u8], values: &mut [u32]) -> usize
+where
+    C: Coder<Elem = u32>,
+    D: CodingDescriptor<Elem = u32>,
+{
+    let tag_len = values.len().div_ceil(4);
+    assert!(input.len() >= tag_len);
+    let (tags, data) = input.split_at(tag_len);
+    let (groups, remainder) = values.split_at_mut(values.len() & !3);
+    let mut read = match prev {
+        Some(p) => coder.decode_deltas(p, &tags[..groups.len() / 4], data, groups),
+        None => coder.decode(&tags[..groups.len() / 4], data, groups),
+    };
+
+    if !remainder.is_empty() {
+        let mut base = prev.map(|p| groups.last().copied().unwrap_or(p));
+        let tag = tags[tag_len - 1];
+        for (i, v) in remainder.iter_mut().enumerate() {
+            let len = D::TAG_LEN[((tag >> (i * 2)) & 0x3) as usize];
+            let mut buf = [0u8; 4];
+            buf[..len].copy_from_slice(&data[read..(read + len)]);
+            read += len;
+            *v = u32::from_le_bytes(buf);
+            if let Some(b) = base {
+                *v = v.wrapping_add(b);
+                base = Some(*v);
+            }
+        }
+    }
+
+    tag_len + read
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{generate_array, generate_cumulative_array};
+
+    // Golden vectors follow the byte layout produced by the C library. tests/lemire/gen_golden.c
+    // prints the same vectors from the C library's encoders to check them against.
+    const GOLDEN_VALUES: [u32; 10] = [
+        0, 1, 255, 256, 65535, 65536, 16777215, 16777216, 0xffffffff, 300,
+    ];
+    const GOLDEN_1234: [u8; 26] = [
+        0x40, 0xe9, 0x07, // control bytes
+        0x00, 0x01, 0xff, 0x00, 0x01, // group 0
+        0xff, 0xff, 0x00, 0x00, 0x01, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01, // group 1
+        0xff, 0xff, 0xff, 0xff, 0x2c, 0x01, // partial group 2
+    ];
+    const GOLDEN_0124: [u8; 27] = [
+        0x94, 0xfe, 0x0b, // control bytes
+        0x01, 0xff, 0x00, 0x01, // group 0
+        0xff, 0xff, 0x00, 0x00, 0x01, 0x00, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00,
+        0x01, // group 1
+        0xff, 0xff, 0xff, 0xff, 0x2c, 0x01, // partial group 2
+    ];
+    const GOLDEN_DELTA_PREV: u32 = 10;
+    const GOLDEN_DELTA_VALUES: [u32; 5] = [10, 11, 266, 266, 70000];
+    const GOLDEN_DELTA: [u8; 9] = [0x00, 0x02, 0x00, 0x01, 0xff, 0x00, 0x66, 0x10, 0x01];
+    // Deltas wrap around when values decrease.
+    const GOLDEN_DELTA_WRAP_VALUES: [u32; 2] = [5, 3];
+    const GOLDEN_DELTA_WRAP: [u8; 6] = [0x0c, 0x05, 0xfe, 0xff, 0xff, 0xff];
+
+    type EncodeFn = fn(&[u32], &mut [u8]) -> usize;
+    type DecodeFn = fn(&[u8], &mut [u32]) -> usize;
+
+    fn check_golden(encode: EncodeFn, decode: DecodeFn, values: &[u32], golden: &[u8]) {
+        let mut encoded = vec![0u8; max_compressed_bytes(values.len())];
+        let len = encode(values, &mut encoded);
+        assert_eq!(golden, &encoded[..len]);
+
+        let mut decoded = vec![0u32; values.len()];
+        assert_eq!(golden.len(), decode(golden, &mut decoded));
+        assert_eq!(values, decoded);
+    }
+
+    #[test]
+    fn golden_1234() {
+        check_golden(encode, decode, &GOLDEN_VALUES, &GOLDEN_1234);
+    }
+
+    #[test]
+    fn golden_0124() {
+        check_golden(encode_0124, decode_0124, &GOLDEN_VALUES, &GOLDEN_0124);
+    }
+
+    #[test]
+    fn golden_deltas() {
+        check_golden(
+            |v, o| encode_deltas(GOLDEN_DELTA_PREV, v, o),
+            |i, v| decode_deltas(GOLDEN_DELTA_PREV, i, v),
+            &GOLDEN_DELTA_VALUES,
+            &GOLDEN_DELTA,
+        );
+        check_golden(
+            |v, o| encode_deltas(0, v, o),
+            |i, v| decode_deltas(0, i, v),
+            &GOLDEN_DELTA_WRAP_VALUES,
+            &GOLDEN_DELTA_WRAP,
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let mut encoded = [0u8; 0];
+        assert_eq!(0, encode(&[], &mut encoded));
+        assert_eq!(0, decode(&encoded, &mut []));
+        assert_eq!(0, encode_deltas(7, &[], &mut encoded));
+        assert_eq!(0, decode_deltas(7, &encoded, &mut []));
+    }
+
+    // A value-at-a-time port of the C library's scalar encoder.
+    fn reference_encode(tag_len: [usize; 4], prev: Option<u32>, values: &[u32]) -> Vec<u8> {
+        let mut tags = vec![0u8; values.len().div_ceil(4)];
+        let mut data = vec![];
+        let mut base = prev;
+        for (i, v) in values.iter().enumerate() {
+            let value = match base {
+                Some(b) => {
+                    base = Some(*v);
+                    v.wrapping_sub(b)
+                }
+                None => *v,
+            };
+            let nbytes = 4 - value.leading_zeros() as usize / 8;
+            let vtag = tag_len.iter().position(|l| *l >= nbytes).unwrap();
+            tags[i / 4] |= (vtag as u8) << ((i % 4) * 2);
+            data.extend_from_slice(&value.to_le_bytes()[..tag_len[vtag]]);
+        }
+        tags.extend_from_slice(&data);
+        tags
+    }
+
+    #[test]
+    fn matches_reference() {
+        for len in 0..=67 {
+            for max_bytes in 1..=4 {
+                let values = generate_array::<u32>(len, max_bytes);
+                let mut encoded = vec![0u8; max_compressed_bytes(len)];
+                let mut decoded = vec![0u32; len];
+
+                let elen = encode(&values, &mut encoded);
+                assert_eq!(
+                    reference_encode([1, 2, 3, 4], None, &values),
+                    encoded[..elen]
+                );
+                assert_eq!(elen, decode(&encoded[..elen], &mut decoded));
+                assert_eq!(values, decoded);
+
+                let elen = encode_0124(&values, &mut encoded);
+                assert_eq!(
+                    reference_encode([0, 1, 2, 4], None, &values),
+                    encoded[..elen]
+                );
+                assert_eq!(elen, decode_0124(&encoded[..elen], &mut decoded));
+                assert_eq!(values, decoded);
+
+                let values = generate_cumulative_array::<u32>(len, max_bytes, 3);
+                let elen = encode_deltas(3, &values, &mut encoded);
+                assert_eq!(
+                    reference_encode([1, 2, 3, 4], Some(3), &values),
+                    encoded[..elen]
+                );
+                assert_eq!(elen, decode_deltas(3, &encoded[..elen], &mut decoded));
+                assert_eq!(values, decoded);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn decode_truncated() {
+        let mut decoded = [0u32; 10];
+        decode(&GOLDEN_1234[..(GOLDEN_1234.len() - 1)], &mut decoded);
+    }
+}
@@ -10,6 +10,9 @@ the implementation will automatically use an accelerated implementation for the
 At the moment group implementations only have acceleration on little-endian `aarch64` targets with
 `NEON` instruction support.
 
+The [`lemire`] module reads and writes the single buffer format produced by the C library, where
+control bytes are followed by data and the number of values need not be a multiple of 4.
+
 ## Example without delta-coding
 
 ```
@@ -75,11 +78,19 @@ let mut group = [0u32; 4];
 coder.decode_deltas(initial, &tags[2..3], &data[skip_data_len..], &mut group);
 assert_eq!(values[11], group[3]);
 ```
+
+For repeated random access into long streams build a `SkipIndex`, which samples data offsets and
+delta bases so that each lookup only touches one block of groups. Single values of streams
+produced by `encode()` may be read with a `RandomAccessReader`. `CompressedSet` and
+`CompressedVec` are owned containers built on the same block layout.
 */
 
 mod arch;
+mod auto;
 mod coder_impl;
 mod coding_descriptor;
+mod dyn_coder;
+mod error;
 mod raw_group;
 mod tag_utils;
 
@@ -87,6 +98,25 @@ mod coder0124;
 mod coder1234;
 mod coder1248;
 
+mod adaptive;
+mod block;
+pub mod lemire;
+pub mod postings;
+mod random_access;
+mod set;
+mod skip_index;
+pub mod stream;
+mod vec;
+
+#[cfg(feature = "capi")]
+pub mod capi;
+
+pub use error::Error;
+
+mod private {
+    /// Supertrait of `Coder` that may only be implemented within this crate.
+    pub trait Sealed {}
+}
 pub use num_traits::{ops::wrapping::WrappingAdd, ops::wrapping::WrappingSub, PrimInt};
 
 /// `Coder` compresses and decompresses integers in a byte-aligned format compose of two streams.
@@ -100,10 +130,16 @@ pub use num_traits::{ops::wrapping::WrappingAdd, ops::wrapping::WrappingSub, Pri
 ///
 /// Use `max_compressed_bytes()` to compute the number of tag and data bytes that must be allocated
 /// to safely encode a slice of input values.
-pub trait Coder: Sized + Copy + Clone {
+///
+/// This trait is sealed and cannot be implemented outside of this crate, so methods may be added
+/// to it without breaking downstream code.
+pub trait Coder: private::Sealed + Sized + Copy + Clone {
     /// The input/output element type for this coder, typically `u32` or `u64`.
     type Elem: PrimInt + WrappingAdd + WrappingSub + std::fmt::Debug + Sized + Copy + Clone;
 
+    /// Stable identifier for the format produced by this coder.
+    const ID: CoderId;
+
     /// Create a new `Coder`, selecting the fastest implementation available.
     ///
     /// These objects should be relatively cheap to create and require no heap allocation.
@@ -111,7 +147,7 @@ pub trait Coder: Sized + Copy + Clone {
 
     /// Returns the number of `(tag_bytes, data_bytes)` required to compress a slice of length `len`.
     fn max_compressed_bytes(len: usize) -> (usize, usize) {
-        let num_groups = (len + 3) / 4;
+        let num_groups = len.div_ceil(4);
         (
             num_groups,
             num_groups * 4 * std::mem::size_of::<Self::Elem>(),
@@ -153,6 +189,177 @@ pub trait Coder: Sized + Copy + Clone {
         data: &mut [u8],
     ) -> usize;
 
+    /// Returns the exact number of data bytes `encode()` would write for `values`.
+    ///
+    /// Only the tag of each group is computed and nothing is written, so this is considerably
+    /// cheaper than encoding when evaluating candidate encodings.
+    ///
+    /// # Panics
+    ///
+    /// - If `values.len() % 4 != 0`
+    fn compressed_len(&self, values: &[Self::Elem]) -> usize;
+
+    /// Returns the exact number of data bytes `encode_deltas()` would write for `values` encoded
+    /// as deltas starting from `initial`.
+    ///
+    /// See `compressed_len()`.
+    ///
+    /// # Panics
+    ///
+    /// - If `values.len() % 4 != 0`
+    fn compressed_len_deltas(&self, initial: Self::Elem, values: &[Self::Elem]) -> usize;
+
+    /// Encodes a slice of values, appending tags and data to growable buffers.
+    ///
+    /// Unlike `encode()` the output does not need to be allocated for the worst case up front.
+    /// Values are encoded a few groups at a time, reserving only enough additional space for the
+    /// worst case of each chunk, and `data` is truncated to the exact encoded length afterwards.
+    ///
+    /// Returns the number of bytes appended to `data`.
+    ///
+    /// # Panics
+    ///
+    /// - If `values.len() % 4 != 0`
+    fn encode_into_vec(
+        &self,
+        values: &[Self::Elem],
+        tags: &mut Vec<u8>,
+        data: &mut Vec<u8>,
+    ) -> usize {
+        encode_into_vec_from(self, None, values, tags, data)
+    }
+
+    /// Encodes a slice of values as deltas starting from `initial`, appending tags and data to
+    /// growable buffers.
+    ///
+    /// See `encode_into_vec()`.
+    ///
+    /// # Panics
+    ///
+    /// - If `values.len() % 4 != 0`
+    fn encode_deltas_into_vec(
+        &self,
+        initial: Self::Elem,
+        values: &[Self::Elem],
+        tags: &mut Vec<u8>,
+        data: &mut Vec<u8>,
+    ) -> usize {
+        encode_into_vec_from(self, Some(initial), values, tags, data)
+    }
+
+    /// Encodes as many leading values of `values` as fit in `data`, writing tags and data to
+    /// separate streams.
+    ///
+    /// Encoding stops at the last group that fits entirely within `data` or when `tags` is full,
+    /// and never writes past the end of `data`. This is useful to fill fixed size pages. If
+    /// `values.len()` is not a multiple of 4 the final partial group is padded with zeros and is
+    /// encoded if it fits.
+    ///
+    /// Returns the number of values consumed and the number of bytes written to the data stream.
+    fn encode_bounded(
+        &self,
+        values: &[Self::Elem],
+        tags: &mut [u8],
+        data: &mut [u8],
+    ) -> (usize, usize) {
+        encode_bounded_from(self, None, values, tags, data)
+    }
+
+    /// Encodes as many leading values of `values` as fit in `data` as deltas starting from
+    /// `initial`.
+    ///
+    /// See `encode_bounded()`. A final partial group is padded by repeating the last value.
+    ///
+    /// Returns the number of values consumed and the number of bytes written to the data stream.
+    fn encode_deltas_bounded(
+        &self,
+        initial: Self::Elem,
+        values: &[Self::Elem],
+        tags: &mut [u8],
+        data: &mut [u8],
+    ) -> (usize, usize) {
+        encode_bounded_from(self, Some(initial), values, tags, data)
+    }
+
+    /// Encodes values produced by an iterator, writing tags and data to separate streams.
+    ///
+    /// Values are copied into a small stack buffer of a few groups at a time and encoded as with
+    /// `encode()`, so no heap allocation is required. At most `tags.len() * 4` values are consumed
+    /// from the iterator; if the number of values is not a multiple of 4 the final group is padded
+    /// with zeros.
+    ///
+    /// Returns the number of values consumed from the iterator and the number of bytes written to
+    /// the data stream.
+    ///
+    /// # Panics
+    ///
+    /// - If `data` is too small to fit all of the output data, as computed by
+    ///   `max_compressed_bytes()`.
+    fn encode_iter<I: IntoIterator<Item = Self::Elem>>(
+        &self,
+        values: I,
+        tags: &mut [u8],
+        data: &mut [u8],
+    ) -> (usize, usize) {
+        encode_iter_from(self, None, values.into_iter(), tags, data)
+    }
+
+    /// Encodes values produced by an iterator as deltas starting from `initial`.
+    ///
+    /// See `encode_iter()`. If the number of values is not a multiple of 4 the final group is
+    /// padded by repeating the last value.
+    ///
+    /// # Panics
+    ///
+    /// - If `data` is too small to fit all of the output data, as computed by
+    ///   `max_compressed_bytes()`.
+    fn encode_deltas_iter<I: IntoIterator<Item = Self::Elem>>(
+        &self,
+        initial: Self::Elem,
+        values: I,
+        tags: &mut [u8],
+        data: &mut [u8],
+    ) -> (usize, usize) {
+        encode_iter_from(self, Some(initial), values.into_iter(), tags, data)
+    }
+
+    /// Encodes a slice of values as deltas, restarting the delta chain from `initial` every
+    /// `restart_interval` values.
+    ///
+    /// Each run of `restart_interval` values is encoded exactly as `encode_deltas()` would encode
+    /// it on its own, so every run may be decoded independently of the values that precede it with
+    /// `decode_deltas(initial, ...)`. The data offset of a run may be computed from the tags with
+    /// `data_len()` or recorded in a `SkipIndex`.
+    ///
+    /// Returns the number of bytes written to the data stream.
+    ///
+    /// # Panics
+    ///
+    /// - If `values.len() % 4 != 0`
+    /// - If `restart_interval` is zero or not a multiple of 4.
+    /// - If `tags` or `data` are too small to fit all of the output data.
+    fn encode_deltas_with_restarts(
+        &self,
+        initial: Self::Elem,
+        restart_interval: usize,
+        values: &[Self::Elem],
+        tags: &mut [u8],
+        data: &mut [u8],
+    ) -> usize {
+        assert!(restart_interval > 0 && restart_interval.is_multiple_of(4));
+        let mut written = 0;
+        for (i, run) in values.chunks(restart_interval).enumerate() {
+            let tag_start = i * restart_interval / 4;
+            written += self.encode_deltas(
+                initial,
+                run,
+                &mut tags[tag_start..(tag_start + run.len() / 4)],
+                &mut data[written..],
+            );
+        }
+        written
+    }
+
     /// Decodes input tags and data streams to an output slice.
     ///
     /// Consumes all tag values in the input stream to produce `tags.len() * 4` values. May consume
@@ -189,9 +396,211 @@ pub trait Coder: Sized + Copy + Clone {
         values: &mut [Self::Elem],
     ) -> usize;
 
+    /// Decodes a stream produced by `encode_deltas_with_restarts()` to an output slice.
+    ///
+    /// Consumes all tag values in the input stream to produce `tags.len() * 4` values.
+    ///
+    /// Returns the number of bytes consumed from the data stream.
+    ///
+    /// # Panics
+    ///
+    /// - If `values.len() % 4 != 0`.
+    /// - If `restart_interval` is zero or not a multiple of 4.
+    /// - If `tags.len() != values.len() / 4`.
+    /// - If decoding would consume bytes past the end of `data`.
+    fn decode_deltas_with_restarts(
+        &self,
+        initial: Self::Elem,
+        restart_interval: usize,
+        tags: &[u8],
+        data: &[u8],
+        values: &mut [Self::Elem],
+    ) -> usize {
+        assert!(restart_interval > 0 && restart_interval.is_multiple_of(4));
+        assert_eq!(tags.len(), values.len() / 4);
+        let mut read = 0;
+        for (run_tags, run) in tags
+            .chunks(restart_interval / 4)
+            .zip(values.chunks_mut(restart_interval))
+        {
+            read += self.decode_deltas(initial, run_tags, &data[read..], run);
+        }
+        read
+    }
+
+    /// Decodes the values at positions `range` of a stream produced by `encode()`.
+    ///
+    /// `range` need not be aligned to group boundaries. The data offset of the first group is
+    /// computed with `data_len()`, partial groups at either end are decoded into a scratch group,
+    /// and exactly `range.len()` values are written to the start of `values`.
+    ///
+    /// # Panics
+    ///
+    /// - If `range.end > tags.len() * 4` or `range.start > range.end`.
+    /// - If `values.len() < range.len()`.
+    /// - If decoding would consume bytes past the end of `data`.
+    fn decode_range(
+        &self,
+        tags: &[u8],
+        data: &[u8],
+        range: std::ops::Range<usize>,
+        values: &mut [Self::Elem],
+    ) {
+        let offset = self.data_len(&tags[..(range.start / 4)]);
+        decode_range_from(self, None, tags, &data[offset..], range, values);
+    }
+
+    /// Decodes the values at positions `range` of a stream produced by `encode_deltas()` from
+    /// `initial`.
+    ///
+    /// Groups before the range are passed over with `skip_deltas()`, then exactly `range.len()`
+    /// values are written to the start of `values` as in `decode_range()`.
+    ///
+    /// # Panics
+    ///
+    /// - If `range.end > tags.len() * 4` or `range.start > range.end`.
+    /// - If `values.len() < range.len()`.
+    /// - If decoding would consume bytes past the end of `data`.
+    fn decode_deltas_range(
+        &self,
+        initial: Self::Elem,
+        tags: &[u8],
+        data: &[u8],
+        range: std::ops::Range<usize>,
+        values: &mut [Self::Elem],
+    ) {
+        let (offset, sum) = self.skip_deltas(&tags[..(range.start / 4)], data);
+        let base = initial.wrapping_add(&sum);
+        decode_range_from(self, Some(base), tags, &data[offset..], range, values);
+    }
+
+    /// Decodes input tags and data streams to uninitialized output memory.
+    ///
+    /// This is equivalent to `decode()` but does not require `values` to be initialized first.
+    /// Every element of `values` is initialized when this method returns; `decode_to_vec()` relies
+    /// on this for soundness, which is why `Coder` is sealed.
+    ///
+    /// Returns the number of bytes consumed from the data stream.
+    ///
+    /// # Panics
+    ///
+    /// - If `tags.len() != values.len() / 4` or `values.len() % 4 != 0`.
+    /// - If decoding would consume bytes past the end of `data`.
+    fn decode_uninit(
+        &self,
+        tags: &[u8],
+        data: &[u8],
+        values: &mut [std::mem::MaybeUninit<Self::Elem>],
+    ) -> usize;
+
+    /// Decodes input tags and data streams as deltas from `initial` to uninitialized output memory.
+    ///
+    /// See `decode_deltas()` and `decode_uninit()`.
+    ///
+    /// # Panics
+    ///
+    /// - If `tags.len() != values.len() / 4` or `values.len() % 4 != 0`.
+    /// - If decoding would consume bytes past the end of `data`.
+    fn decode_deltas_uninit(
+        &self,
+        initial: Self::Elem,
+        tags: &[u8],
+        data: &[u8],
+        values: &mut [std::mem::MaybeUninit<Self::Elem>],
+    ) -> usize;
+
+    /// Decodes input tags and data streams, appending `tags.len() * 4` values to `values`.
+    ///
+    /// Values are written directly into the spare capacity of `values` without initializing it.
+    ///
+    /// Returns the number of bytes consumed from the data stream.
+    ///
+    /// # Panics
+    ///
+    /// - If decoding would consume bytes past the end of `data`.
+    fn decode_to_vec(&self, tags: &[u8], data: &[u8], values: &mut Vec<Self::Elem>) -> usize {
+        let len = tags.len() * 4;
+        values.reserve(len);
+        let read = self.decode_uninit(tags, data, &mut values.spare_capacity_mut()[..len]);
+        // SAFETY: Coder is sealed and every implementation of decode_uninit() in this crate either
+        // initializes all of its output or panics, so the first len elements of spare capacity are
+        // initialized.
+        unsafe { values.set_len(values.len() + len) };
+        read
+    }
+
+    /// Decodes input tags and data streams as deltas from `initial`, appending `tags.len() * 4`
+    /// values to `values`.
+    ///
+    /// See `decode_to_vec()`.
+    ///
+    /// # Panics
+    ///
+    /// - If decoding would consume bytes past the end of `data`.
+    fn decode_deltas_to_vec(
+        &self,
+        initial: Self::Elem,
+        tags: &[u8],
+        data: &[u8],
+        values: &mut Vec<Self::Elem>,
+    ) -> usize {
+        let len = tags.len() * 4;
+        values.reserve(len);
+        let read =
+            self.decode_deltas_uninit(initial, tags, data, &mut values.spare_capacity_mut()[..len]);
+        // SAFETY: as in decode_to_vec(), decode_deltas_uninit() initialized the first len elements
+        // of spare capacity.
+        unsafe { values.set_len(values.len() + len) };
+        read
+    }
+
+    /// Decodes tag and data streams that are each split across a list of segments.
+    ///
+    /// This is equivalent to calling `decode()` on the concatenation of all `tags` segments and the
+    /// concatenation of all `data` segments, but without copying the input. Segment boundaries in
+    /// the tag and data streams are independent of each other and of group boundaries.
+    ///
+    /// Returns the number of bytes consumed from all of the data segments.
+    ///
+    /// # Panics
+    ///
+    /// - If `values.len() % 4 != 0`.
+    /// - If the total length of `tags` is not equal to `values.len() / 4`.
+    /// - If decoding would consume bytes past the end of the last data segment.
+    fn decode_segments(&self, tags: &[&[u8]], data: &[&[u8]], values: &mut [Self::Elem]) -> usize;
+
+    /// Decodes tag and data streams that are each split across a list of segments.
+    ///
+    /// Values are interpreted as deltas starting from `initial`. See `decode_segments()`.
+    ///
+    /// # Panics
+    ///
+    /// - If `values.len() % 4 != 0`.
+    /// - If the total length of `tags` is not equal to `values.len() / 4`.
+    /// - If decoding would consume bytes past the end of the last data segment.
+    fn decode_deltas_segments(
+        &self,
+        initial: Self::Elem,
+        tags: &[&[u8]],
+        data: &[&[u8]],
+        values: &mut [Self::Elem],
+    ) -> usize;
+
     /// Returns the data length of all the groups encoded by `tags`.
     fn data_len(&self, tags: &[u8]) -> usize;
 
+    /// Decode only the value at `index` within a single group encoded with `tag`.
+    ///
+    /// `data` must start at the beginning of the group's encoded bytes, as located with
+    /// `data_len()`. The position of the value within the group is computed from `tag`, so the
+    /// rest of the group is not decoded.
+    ///
+    /// # Panics
+    ///
+    /// - If `index >= 4`.
+    /// - If the value extends past the end of `data`.
+    fn decode_value(&self, tag: u8, data: &[u8], index: usize) -> Self::Elem;
+
     /// Skip `tags.len() * 4` deltas read from input tag and data streams.
     ///
     /// Returns the number of bytes consumed from the data stream and the sum of all the deltas that
@@ -201,11 +610,306 @@ pub trait Coder: Sized + Copy + Clone {
     ///
     ///  - If decoding would consume bytes past the end of `data`.
     fn skip_deltas(&self, tags: &[u8], data: &[u8]) -> (usize, Self::Elem);
+
+    /// Find the first value greater than or equal to `target` in a delta coded stream.
+    ///
+    /// The stream must have been produced by `encode_deltas()` from `initial` over values sorted
+    /// in non-decreasing order. Whole chunks of groups are skipped without decoding them while
+    /// their values remain below `target`, so this is considerably faster than `decode_deltas()`
+    /// followed by a search.
+    ///
+    /// Returns the index and value of the first match, or `None` if all `tags.len() * 4` values
+    /// are less than `target`.
+    ///
+    /// # Panics
+    ///
+    ///  - If decoding would consume bytes past the end of `data`.
+    fn find_ge(
+        &self,
+        initial: Self::Elem,
+        tags: &[u8],
+        data: &[u8],
+        target: Self::Elem,
+    ) -> Option<(usize, Self::Elem)>;
+
+    /// Append every value of `values` that also appears in a delta coded stream to `out`.
+    ///
+    /// The stream must have been produced by `encode_deltas()` from `initial` over values sorted
+    /// in non-decreasing order, and `values` must be sorted in strictly increasing order. Groups
+    /// of the stream that cannot contain any of `values` are skipped without being decoded, so the
+    /// cost is proportional to `values.len()` rather than the length of the stream when `values`
+    /// is small. See `postings::intersect()` to intersect several streams.
+    ///
+    /// # Panics
+    ///
+    ///  - If decoding would consume bytes past the end of `data`.
+    fn intersect_deltas(
+        &self,
+        initial: Self::Elem,
+        tags: &[u8],
+        data: &[u8],
+        values: &[Self::Elem],
+        out: &mut Vec<Self::Elem>,
+    );
+
+    /// Skip deltas read from tag and data streams that are each split across a list of segments.
+    ///
+    /// See `skip_deltas()` and `decode_segments()`.
+    ///
+    /// # Panics
+    ///
+    ///  - If decoding would consume bytes past the end of the last data segment.
+    fn skip_deltas_segments(&self, tags: &[&[u8]], data: &[&[u8]]) -> (usize, Self::Elem);
+}
+
+/// Number of groups encoded per output reservation by `encode_into_vec_from()`.
+const ENCODE_VEC_GROUPS: usize = 8;
+
+/// Encode `values` in chunks of `ENCODE_VEC_GROUPS`, growing `data` by the worst case length of
+/// one chunk at a time. If `base` is set values are encoded as deltas from `base`.
+fn encode_into_vec_from<C: Coder>(
+    coder: &C,
+    mut base: Option<C::Elem>,
+    values: &[C::Elem],
+    tags: &mut Vec<u8>,
+    data: &mut Vec<u8>,
+) -> usize {
+    assert!(values.len().is_multiple_of(4));
+    let tag_start = tags.len();
+    let data_start = data.len();
+    tags.resize(tag_start + values.len() / 4, 0);
+    let chunk_tags = tags[tag_start..].chunks_mut(ENCODE_VEC_GROUPS);
+    for (chunk, chunk_tags) in values.chunks(ENCODE_VEC_GROUPS * 4).zip(chunk_tags) {
+        let start = data.len();
+        data.resize(start + C::max_compressed_bytes(chunk.len()).1, 0);
+        let written = match base {
+            Some(b) => {
+                base = Some(chunk[chunk.len() - 1]);
+                coder.encode_deltas(b, chunk, chunk_tags, &mut data[start..])
+            }
+            None => coder.encode(chunk, chunk_tags, &mut data[start..]),
+        };
+        data.truncate(start + written);
+    }
+    data.len() - data_start
+}
+
+/// Encode the longest prefix of `values` that fits in `data`. If `base` is set values are
+/// encoded as deltas from `base`.
+///
+/// Whole chunks of `ENCODE_VEC_GROUPS` are encoded in place while `data` has room for their worst
+/// case length; the remaining groups are encoded one at a time into a scratch buffer and copied
+/// only if they fit.
+fn encode_bounded_from<C: Coder>(
+    coder: &C,
+    mut base: Option<C::Elem>,
+    values: &[C::Elem],
+    tags: &mut [u8],
+    data: &mut [u8],
+) -> (usize, usize) {
+    let chunk_len = ENCODE_VEC_GROUPS * 4;
+    let chunk_max_data = C::max_compressed_bytes(chunk_len).1;
+    let mut consumed = 0;
+    let mut written = 0;
+    let mut tag_index = 0;
+    while values.len() - consumed >= chunk_len
+        && tags.len() - tag_index >= ENCODE_VEC_GROUPS
+        && data.len() - written >= chunk_max_data
+    {
+        let chunk = &values[consumed..(consumed + chunk_len)];
+        let chunk_tags = &mut tags[tag_index..(tag_index + ENCODE_VEC_GROUPS)];
+        written += match base {
+            Some(b) => {
+                base = Some(chunk[chunk_len - 1]);
+                coder.encode_deltas(b, chunk, chunk_tags, &mut data[written..])
+            }
+            None => coder.encode(chunk, chunk_tags, &mut data[written..]),
+        };
+        consumed += chunk_len;
+        tag_index += ENCODE_VEC_GROUPS;
+    }
+
+    let mut scratch = [0u8; 32];
+    let scratch = &mut scratch[..C::max_compressed_bytes(4).1];
+    while consumed < values.len() && tag_index < tags.len() {
+        let n = std::cmp::min(4, values.len() - consumed);
+        let mut group = [<C::Elem as num_traits::Zero>::zero(); 4];
+        group[..n].copy_from_slice(&values[consumed..(consumed + n)]);
+        let mut tag = [0u8; 1];
+        let len = match base {
+            Some(b) => {
+                let last = group[n - 1];
+                group[n..].fill(last);
+                coder.encode_deltas(b, &group, &mut tag, scratch)
+            }
+            None => coder.encode(&group, &mut tag, scratch),
+        };
+        if data.len() - written < len {
+            break;
+        }
+        data[written..(written + len)].copy_from_slice(&scratch[..len]);
+        tags[tag_index] = tag[0];
+        base = base.map(|_| group[3]);
+        consumed += n;
+        written += len;
+        tag_index += 1;
+    }
+    (consumed, written)
+}
+
+/// Number of groups buffered on the stack by `encode_iter_from()`.
+const ENCODE_ITER_GROUPS: usize = 8;
+
+/// Encode values from an iterator in chunks of `ENCODE_ITER_GROUPS`. If `base` is set values are
+/// encoded as deltas from `base`.
+fn encode_iter_from<C: Coder, I: Iterator<Item = C::Elem>>(
+    coder: &C,
+    mut base: Option<C::Elem>,
+    mut values: I,
+    tags: &mut [u8],
+    data: &mut [u8],
+) -> (usize, usize) {
+    let mut buf = [<C::Elem as num_traits::Zero>::zero(); ENCODE_ITER_GROUPS * 4];
+    let mut consumed = 0;
+    let mut written = 0;
+    for tag_chunk in tags.chunks_mut(ENCODE_ITER_GROUPS) {
+        let cap = tag_chunk.len() * 4;
+        let mut n = 0usize;
+        for (slot, v) in buf[..cap].iter_mut().zip(values.by_ref()) {
+            *slot = v;
+            n += 1;
+        }
+        if n == 0 {
+            break;
+        }
+
+        let len = n.div_ceil(4) * 4;
+        let num_groups = len / 4;
+        written += match base {
+            Some(b) => {
+                let last = buf[n - 1];
+                buf[n..len].fill(last);
+                base = Some(last);
+                coder.encode_deltas(
+                    b,
+                    &buf[..len],
+                    &mut tag_chunk[..num_groups],
+                    &mut data[written..],
+                )
+            }
+            None => {
+                buf[n..len].fill(<C::Elem as num_traits::Zero>::zero());
+                coder.encode(
+                    &buf[..len],
+                    &mut tag_chunk[..num_groups],
+                    &mut data[written..],
+                )
+            }
+        };
+        consumed += n;
+        if n < cap {
+            break;
+        }
+    }
+    (consumed, written)
+}
+
+/// Decode the values at positions `range` of a stream where `data` begins at the group containing
+/// `range.start`. If `base` is set values are decoded as deltas from `base`.
+fn decode_range_from<C: Coder>(
+    coder: &C,
+    mut base: Option<C::Elem>,
+    tags: &[u8],
+    data: &[u8],
+    range: std::ops::Range<usize>,
+    values: &mut [C::Elem],
+) {
+    assert!(range.start <= range.end && range.end <= tags.len() * 4);
+    let values = &mut values[..range.len()];
+    if values.is_empty() {
+        return;
+    }
+    let mut decode = |tags: &[u8], data: &[u8], out: &mut [C::Elem]| {
+        let read = match base {
+            Some(b) => coder.decode_deltas(b, tags, data, out),
+            None => coder.decode(tags, data, out),
+        };
+        if let Some(b) = base.as_mut() {
+            *b = out[out.len() - 1];
+        }
+        read
+    };
+
+    let mut group = range.start / 4;
+    let mut offset = 0;
+    let mut written = 0;
+    let mut scratch = [<C::Elem as num_traits::Zero>::zero(); 4];
+    let skip = range.start % 4;
+    if skip != 0 {
+        offset += decode(&tags[group..(group + 1)], data, &mut scratch);
+        written = std::cmp::min(4 - skip, values.len());
+        values[..written].copy_from_slice(&scratch[skip..(skip + written)]);
+        group += 1;
+    }
+
+    let whole = (values.len() - written) / 4;
+    if whole > 0 {
+        let end = written + whole * 4;
+        offset += decode(
+            &tags[group..(group + whole)],
+            &data[offset..],
+            &mut values[written..end],
+        );
+        written = end;
+        group += whole;
+    }
+
+    if written < values.len() {
+        decode(&tags[group..(group + 1)], &data[offset..], &mut scratch);
+        let rest = values.len() - written;
+        values[written..].copy_from_slice(&scratch[..rest]);
+    }
+}
+
+/// Stable numeric identifier for each `Coder` format.
+///
+/// These values are written to serialized output and will not change between releases.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CoderId {
+    /// Identifies `Coder0124`.
+    Coder0124 = 1,
+    /// Identifies `Coder1234`.
+    Coder1234 = 2,
+    /// Identifies `Coder1248`.
+    Coder1248 = 3,
+}
+
+impl TryFrom<u8> for CoderId {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(CoderId::Coder0124),
+            2 => Ok(CoderId::Coder1234),
+            3 => Ok(CoderId::Coder1248),
+            _ => Err(Error::UnknownCoder(value)),
+        }
+    }
 }
 
+pub use adaptive::{AdaptiveBlock, AdaptiveStream};
+pub use auto::AutoCoder;
+pub use block::{BlockElem, EncodedBlock, EncodedBlockRef};
 pub use coder0124::Coder0124;
 pub use coder1234::Coder1234;
 pub use coder1248::Coder1248;
+pub use dyn_coder::{DynCoder32, DynCoder64};
+pub use random_access::RandomAccessReader;
+pub use set::{CompressedSet, CompressedU32Set, CompressedU64Set};
+pub use skip_index::SkipIndex;
+pub use stream::{PushDecoder, StreamDecoder, StreamEncoder};
+pub use vec::CompressedVec;
 
 #[cfg(test)]
 pub(crate) mod tests;
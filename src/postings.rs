@@ -0,0 +1,526 @@
+//! Set operations on sorted lists of integers that are stored delta coded.
+//!
+//! Posting lists in search indexes are typically sorted document ids stored with
+//! `Coder::encode_deltas()`. These functions operate on the encoded tag and data streams directly
+//! and avoid decoding groups that cannot contribute to the result.
+
+use crate::Coder;
+use num_traits::Zero;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// A strictly increasing list of values delta coded with `Coder::encode_deltas()`.
+///
+/// The list borrows its tag and data streams. Lists whose length is not a multiple of 4 should
+/// pad the final group by repeating the last value, as `EncodedBlock::encode_deltas()` does; the
+/// padding is dropped using the length of the list.
+#[derive(Copy, Clone, Debug)]
+pub struct PostingList<'a, E> {
+    initial: E,
+    len: usize,
+    tags: &'a [u8],
+    data: &'a [u8],
+}
+
+impl<'a, E: Copy> PostingList<'a, E> {
+    /// Create a list of `len` values from tag and data streams that were delta coded from `initial`.
+    ///
+    /// # Panics
+    ///
+    ///  - If `tags` does not contain exactly `len.div_ceil(4)` groups.
+    pub fn new(initial: E, len: usize, tags: &'a [u8], data: &'a [u8]) -> Self {
+        assert_eq!(len.div_ceil(4), tags.len(), "tags do not match len");
+        Self {
+            initial,
+            len,
+            tags,
+            data,
+        }
+    }
+
+    /// Returns the value the list was delta coded from.
+    pub fn initial(&self) -> E {
+        self.initial
+    }
+
+    /// Returns the number of values in the list, excluding padding.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the list contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the tag stream.
+    pub fn tags(&self) -> &'a [u8] {
+        self.tags
+    }
+
+    /// Returns the data stream.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// Returns the values that appear in every one of `lists` in increasing order.
+///
+/// The shortest list is decoded in full and then intersected with each of the remaining lists in
+/// order of increasing length using `Coder::intersect_deltas()`, which gallops over groups of the
+/// longer list and only decodes candidate groups.
+///
+/// ```
+/// use streamvbyte64::{Coder, Coder1234, postings::{intersect, PostingList}};
+///
+/// fn encode(coder: &Coder1234, values: &[u32]) -> (Vec<u8>, Vec<u8>) {
+///     let (tag_len, data_len) = Coder1234::max_compressed_bytes(values.len());
+///     let mut tags = vec![0u8; tag_len];
+///     let mut data = vec![0u8; data_len];
+///     let data_len = coder.encode_deltas(0, values, &mut tags, &mut data);
+///     data.truncate(data_len);
+///     (tags, data)
+/// }
+///
+/// let coder = Coder1234::new();
+/// let evens = (0..10_000u32).map(|v| v * 2).collect::<Vec<_>>();
+/// let few = [3u32, 10, 15, 9998];
+/// let (etags, edata) = encode(&coder, &evens);
+/// let (ftags, fdata) = encode(&coder, &few);
+/// let lists = [
+///     PostingList::new(0, evens.len(), &etags, &edata),
+///     PostingList::new(0, few.len(), &ftags, &fdata),
+/// ];
+/// assert_eq!(vec![10, 9998], intersect(&coder, &lists));
+/// ```
+///
+/// # Panics
+///
+///  - If decoding any list would consume bytes past the end of its data stream.
+pub fn intersect<C: Coder>(coder: &C, lists: &[PostingList<'_, C::Elem>]) -> Vec<C::Elem> {
+    let mut order = lists.iter().collect::<Vec<_>>();
+    order.sort_by_key(|l| l.len);
+    let (first, rest) = match order.split_first() {
+        Some(split) => split,
+        None => return vec![],
+    };
+
+    let mut values = vec![C::Elem::zero(); first.tags.len() * 4];
+    coder.decode_deltas(first.initial, first.tags, first.data, &mut values);
+    // Drop any padding in the final group.
+    values.truncate(first.len);
+    for list in rest {
+        if values.is_empty() {
+            break;
+        }
+        let mut out = Vec::with_capacity(values.len());
+        coder.intersect_deltas(list.initial, list.tags, list.data, &values, &mut out);
+        values = out;
+    }
+    values
+}
+
+/// Selects which values `merge()` writes to its output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MergeMode {
+    /// Values that appear in any list.
+    Union,
+    /// Values in the first list that do not appear in any other list.
+    Difference,
+    /// Values that appear in an odd number of lists.
+    SymmetricDifference,
+}
+
+/// An owned delta coded list produced by `merge()`.
+///
+/// The list is delta coded from zero and the final group is padded by repeating the last value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnedPostingList<E> {
+    len: usize,
+    tags: Vec<u8>,
+    data: Vec<u8>,
+    initial: E,
+}
+
+impl<E: Copy> OwnedPostingList<E> {
+    /// Returns the number of values in the list, excluding padding.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the list contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the tag stream.
+    pub fn tags(&self) -> &[u8] {
+        &self.tags
+    }
+
+    /// Returns the data stream.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Borrow this list for use as input to `intersect()` or `merge()`.
+    pub fn as_list(&self) -> PostingList<'_, E> {
+        PostingList::new(self.initial, self.len, &self.tags, &self.data)
+    }
+
+    /// Returns the length, tag stream and data stream of this list.
+    pub(crate) fn into_parts(self) -> (usize, Vec<u8>, Vec<u8>) {
+        (self.len, self.tags, self.data)
+    }
+}
+
+/// Number of groups decoded at a time by each merge cursor.
+const CURSOR_BATCH_GROUPS: usize = 64;
+/// Number of values buffered before encoding merge output.
+const WRITER_BATCH_LEN: usize = 1024;
+
+/// Iterates over the values of a `PostingList`, decoding a batch of groups at a time.
+struct Cursor<'a, C: Coder> {
+    list: PostingList<'a, C::Elem>,
+    tag_index: usize,
+    read: usize,
+    /// Number of values returned so far, used to stop before padding in the final group.
+    returned: usize,
+    buf: Vec<C::Elem>,
+    pos: usize,
+}
+
+impl<'a, C: Coder> Cursor<'a, C> {
+    fn new(list: PostingList<'a, C::Elem>) -> Self {
+        Self {
+            list,
+            tag_index: 0,
+            read: 0,
+            returned: 0,
+            buf: Vec::with_capacity(CURSOR_BATCH_GROUPS * 4),
+            pos: 0,
+        }
+    }
+
+    fn next(&mut self, coder: &C) -> Option<C::Elem> {
+        if self.returned == self.list.len {
+            return None;
+        }
+        if self.pos == self.buf.len() {
+            self.fill(coder);
+        }
+        let v = self.buf[self.pos];
+        self.pos += 1;
+        self.returned += 1;
+        Some(v)
+    }
+
+    fn fill(&mut self, coder: &C) {
+        let tags = &self.list.tags[self.tag_index..];
+        let tags = &tags[..std::cmp::min(tags.len(), CURSOR_BATCH_GROUPS)];
+        let base = self.buf.last().copied().unwrap_or(self.list.initial);
+        self.buf.clear();
+        self.buf.resize(tags.len() * 4, C::Elem::zero());
+        self.read += coder.decode_deltas(base, tags, &self.list.data[self.read..], &mut self.buf);
+        self.tag_index += tags.len();
+        self.pos = 0;
+    }
+}
+
+/// Accumulates sorted values and encodes them to an `OwnedPostingList` in batches.
+pub(crate) struct Writer<'a, C: Coder> {
+    coder: &'a C,
+    buf: Vec<C::Elem>,
+    /// Last value encoded to `out`, which is the delta base for the next batch.
+    base: C::Elem,
+    out: OwnedPostingList<C::Elem>,
+}
+
+impl<'a, C: Coder> Writer<'a, C> {
+    pub(crate) fn new(coder: &'a C) -> Self {
+        Self {
+            coder,
+            buf: Vec::with_capacity(WRITER_BATCH_LEN),
+            base: C::Elem::zero(),
+            out: OwnedPostingList {
+                len: 0,
+                tags: vec![],
+                data: vec![],
+                initial: C::Elem::zero(),
+            },
+        }
+    }
+
+    pub(crate) fn push(&mut self, value: C::Elem) {
+        self.buf.push(value);
+        self.out.len += 1;
+        if self.buf.len() == WRITER_BATCH_LEN {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        let last = match self.buf.last() {
+            Some(v) => *v,
+            None => return,
+        };
+        while !self.buf.len().is_multiple_of(4) {
+            self.buf.push(last);
+        }
+        self.coder.encode_deltas_into_vec(
+            self.base,
+            &self.buf,
+            &mut self.out.tags,
+            &mut self.out.data,
+        );
+        self.base = last;
+        self.buf.clear();
+    }
+
+    pub(crate) fn finish(mut self) -> OwnedPostingList<C::Elem> {
+        self.flush();
+        self.out
+    }
+}
+
+/// Merge sorted `lists` into a new delta coded list of distinct values selected by `mode`.
+///
+/// Each input list is read through a cursor that decodes a small batch of groups at a time and
+/// output is encoded in batches with `Coder::encode_deltas()`, so memory use does not depend on
+/// the length of the inputs.
+///
+/// ```
+/// use streamvbyte64::{Coder, Coder1234, EncodedBlock};
+/// use streamvbyte64::postings::{merge, MergeMode, PostingList};
+///
+/// let coder = Coder1234::new();
+/// let a = EncodedBlock::encode_deltas(&coder, 0, &[1u32, 3, 5, 7]);
+/// let b = EncodedBlock::encode_deltas(&coder, 0, &[2u32, 3, 4]);
+/// let lists = [
+///     PostingList::new(0, a.len(), a.tags(), a.data()),
+///     PostingList::new(0, b.len(), b.tags(), b.data()),
+/// ];
+///
+/// let merged = merge(&coder, &lists, MergeMode::Union);
+/// assert_eq!(6, merged.len());
+/// let mut values = vec![0u32; merged.tags().len() * 4];
+/// coder.decode_deltas(0, merged.tags(), merged.data(), &mut values);
+/// assert_eq!(&[1, 2, 3, 4, 5, 7], &values[..6]);
+/// ```
+///
+/// # Panics
+///
+///  - If decoding any list would consume bytes past the end of its data stream.
+pub fn merge<C: Coder>(
+    coder: &C,
+    lists: &[PostingList<'_, C::Elem>],
+    mode: MergeMode,
+) -> OwnedPostingList<C::Elem> {
+    let mut cursors = lists
+        .iter()
+        .map(|l| Cursor::<C>::new(*l))
+        .collect::<Vec<_>>();
+    let mut heap = BinaryHeap::with_capacity(cursors.len());
+    for (i, cursor) in cursors.iter_mut().enumerate() {
+        if let Some(v) = cursor.next(coder) {
+            heap.push(Reverse((v, i)));
+        }
+    }
+
+    let mut writer = Writer::new(coder);
+    // Cursors positioned at the current value, reused across values to avoid allocating.
+    let mut advance = Vec::with_capacity(cursors.len());
+    while let Some(Reverse((value, first))) = heap.pop() {
+        let mut count = 1;
+        let mut in_first = first == 0;
+        advance.clear();
+        advance.push(first);
+        while let Some(Reverse((v, i))) = heap.peek().copied() {
+            if v != value {
+                break;
+            }
+            heap.pop();
+            count += 1;
+            in_first |= i == 0;
+            advance.push(i);
+        }
+        for &i in &advance {
+            if let Some(v) = cursors[i].next(coder) {
+                heap.push(Reverse((v, i)));
+            }
+        }
+
+        let emit = match mode {
+            MergeMode::Union => true,
+            MergeMode::Difference => in_first && count == 1,
+            MergeMode::SymmetricDifference => count % 2 == 1,
+        };
+        if emit {
+            writer.push(value);
+        }
+    }
+    writer.finish()
+}
+
+/// Merge sorted `lists` into a new delta coded list containing every distinct value.
+///
+/// This is `merge()` with `MergeMode::Union`.
+pub fn merge_union<C: Coder>(
+    coder: &C,
+    lists: &[PostingList<'_, C::Elem>],
+) -> OwnedPostingList<C::Elem> {
+    merge(coder, lists, MergeMode::Union)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Coder0124, Coder1234, Coder1248, EncodedBlock};
+    use num_traits::NumCast;
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    /// Generate a sorted set of `len` values less than `max`.
+    fn generate_set(rng: &mut StdRng, len: usize, max: u32) -> BTreeSet<u32> {
+        let mut set = BTreeSet::new();
+        while set.len() < len {
+            set.insert(rng.gen_range(1..max));
+        }
+        set
+    }
+
+    fn check<C: Coder>(coder: C) {
+        let mut rng = StdRng::from_seed([0x5au8; 32]);
+        for lens in [
+            vec![0, 100],
+            vec![1, 1000],
+            vec![7, 10000],
+            vec![333, 5000],
+            vec![2000, 2000],
+            vec![50, 3000, 20000],
+            vec![1000, 1000, 1000, 1000],
+        ] {
+            for max in [4000, 100_000] {
+                let sets = lens
+                    .iter()
+                    .map(|l| generate_set(&mut rng, std::cmp::min(*l, max as usize / 2), max))
+                    .collect::<Vec<_>>();
+                let blocks = sets
+                    .iter()
+                    .map(|s| {
+                        let values = s
+                            .iter()
+                            .map(|v| <C::Elem as NumCast>::from(*v).unwrap())
+                            .collect::<Vec<_>>();
+                        EncodedBlock::encode_deltas(&coder, C::Elem::zero(), &values)
+                    })
+                    .collect::<Vec<_>>();
+                let lists = blocks
+                    .iter()
+                    .map(|b| PostingList::new(C::Elem::zero(), b.len(), b.tags(), b.data()))
+                    .collect::<Vec<_>>();
+
+                let expected = sets
+                    .iter()
+                    .skip(1)
+                    .fold(sets[0].clone(), |acc, s| &acc & s)
+                    .into_iter()
+                    .map(|v| <C::Elem as NumCast>::from(v).unwrap())
+                    .collect::<Vec<_>>();
+                assert_eq!(expected, intersect(&coder, &lists), "lens={:?}", lens);
+            }
+        }
+    }
+
+    #[test]
+    fn intersect_coder0124() {
+        check(Coder0124::new());
+    }
+
+    #[test]
+    fn intersect_coder1234() {
+        check(Coder1234::new());
+    }
+
+    #[test]
+    fn intersect_coder1248() {
+        check(Coder1248::new());
+    }
+
+    fn check_merge<C: Coder>(coder: C) {
+        let mut rng = StdRng::from_seed([0xa5u8; 32]);
+        for lens in [
+            vec![],
+            vec![5],
+            vec![0, 100],
+            vec![3, 3000, 1500],
+            vec![1000; 5],
+        ] {
+            let sets = lens
+                .iter()
+                .map(|l| generate_set(&mut rng, *l, 10_000))
+                .collect::<Vec<_>>();
+            let blocks = sets
+                .iter()
+                .map(|s| {
+                    let values = s
+                        .iter()
+                        .map(|v| <C::Elem as NumCast>::from(*v).unwrap())
+                        .collect::<Vec<_>>();
+                    EncodedBlock::encode_deltas(&coder, C::Elem::zero(), &values)
+                })
+                .collect::<Vec<_>>();
+            let lists = blocks
+                .iter()
+                .map(|b| PostingList::new(C::Elem::zero(), b.len(), b.tags(), b.data()))
+                .collect::<Vec<_>>();
+
+            let empty = BTreeSet::new();
+            let first = sets.first().unwrap_or(&empty);
+            let union = sets.iter().fold(BTreeSet::new(), |acc, s| &acc | s);
+            let rest = sets.iter().skip(1).fold(BTreeSet::new(), |acc, s| &acc | s);
+            let sym = sets.iter().fold(BTreeSet::new(), |acc, s| &acc ^ s);
+            for (mode, expected) in [
+                (MergeMode::Union, union),
+                (MergeMode::Difference, first - &rest),
+                (MergeMode::SymmetricDifference, sym),
+            ] {
+                let merged = merge(&coder, &lists, mode);
+                assert_eq!(expected.len(), merged.len(), "{:?} lens={:?}", mode, lens);
+                let mut values = vec![C::Elem::zero(); merged.tags().len() * 4];
+                coder.decode_deltas(C::Elem::zero(), merged.tags(), merged.data(), &mut values);
+                values.truncate(merged.len());
+                let expected = expected
+                    .into_iter()
+                    .map(|v| <C::Elem as NumCast>::from(v).unwrap())
+                    .collect::<Vec<_>>();
+                assert_eq!(expected, values, "{:?} lens={:?}", mode, lens);
+                // The output may be used as input to further operations.
+                if !merged.is_empty() {
+                    assert_eq!(expected, intersect(&coder, &[merged.as_list()]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn merge_coder0124() {
+        check_merge(Coder0124::new());
+    }
+
+    #[test]
+    fn merge_coder1234() {
+        check_merge(Coder1234::new());
+    }
+
+    #[test]
+    fn merge_coder1248() {
+        check_merge(Coder1248::new());
+    }
+
+    #[test]
+    fn intersect_empty() {
+        let coder = Coder1234::new();
+        assert!(intersect(&coder, &[]).is_empty());
+    }
+}
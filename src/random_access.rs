@@ -0,0 +1,123 @@
+//! Random access to individual values of a plain encoded stream.
+
+use crate::{Coder, SkipIndex};
+
+/// Number of groups covered by each entry of the skip index.
+const OFFSET_INTERVAL: usize = 64;
+
+/// A reader that extracts single values from a stream produced by `Coder::encode()`.
+///
+/// Locating a value with `data_len(&tags[..n])` costs time proportional to `n`. The reader keeps
+/// a `SkipIndex` with the data offset of every 64th group, so each lookup sums the lengths of at
+/// most 63 tags and then extracts the value from its group using the tag byte, without decoding
+/// the rest of the group. The index costs 8 bytes per 256 values.
+///
+/// ```
+/// use streamvbyte64::{Coder, Coder1234, RandomAccessReader};
+///
+/// let coder = Coder1234::new();
+/// let values = (0..4096u32).map(|v| v * v).collect::<Vec<_>>();
+/// let (tag_len, data_len) = Coder1234::max_compressed_bytes(values.len());
+/// let mut tags = vec![0u8; tag_len];
+/// let mut data = vec![0u8; data_len];
+/// let data_len = coder.encode(&values, &mut tags, &mut data);
+/// data.truncate(data_len);
+///
+/// let reader = RandomAccessReader::new(coder, &tags, &data);
+/// assert_eq!(Some(3001 * 3001), reader.get(3001));
+/// assert_eq!(None, reader.get(4096));
+/// ```
+#[derive(Clone)]
+pub struct RandomAccessReader<'a, C: Coder> {
+    coder: C,
+    tags: &'a [u8],
+    data: &'a [u8],
+    index: SkipIndex<C>,
+}
+
+impl<'a, C: Coder> RandomAccessReader<'a, C> {
+    /// Create a reader over `tags` and `data` encoded by `coder`.
+    ///
+    /// # Panics
+    ///
+    /// - If `data` is shorter than the length described by `tags`.
+    pub fn new(coder: C, tags: &'a [u8], data: &'a [u8]) -> Self {
+        Self {
+            coder,
+            tags,
+            data,
+            index: SkipIndex::new(coder, tags, data, OFFSET_INTERVAL),
+        }
+    }
+
+    /// Returns the number of values in the stream.
+    pub fn len(&self) -> usize {
+        self.tags.len() * 4
+    }
+
+    /// Returns true if the stream contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// Returns the value at `index`, or `None` if `index >= len()`.
+    pub fn get(&self, index: usize) -> Option<C::Elem> {
+        let group = index / 4;
+        let tag = *self.tags.get(group)?;
+        let (offset, _) = self.index.seek(self.tags, self.data, group);
+        Some(
+            self.coder
+                .decode_value(tag, &self.data[offset..], index % 4),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::generate_array;
+    use crate::{Coder0124, Coder1234, Coder1248};
+
+    fn check<C: Coder>(coder: C) {
+        for len in [0, 4, 252, 256, 260, 4096] {
+            let expected = generate_array::<C::Elem>(len, std::mem::size_of::<C::Elem>());
+            let (tag_len, data_len) = C::max_compressed_bytes(len);
+            let mut tags = vec![0u8; tag_len];
+            let mut data = vec![0u8; data_len];
+            let data_len = coder.encode(&expected, &mut tags, &mut data);
+            data.truncate(data_len);
+
+            let reader = RandomAccessReader::new(coder, &tags, &data);
+            assert_eq!(len, reader.len());
+            assert_eq!(len == 0, reader.is_empty());
+            for (i, v) in expected.iter().enumerate() {
+                assert_eq!(Some(*v), reader.get(i), "len {} index {}", len, i);
+            }
+            assert_eq!(None, reader.get(len));
+        }
+    }
+
+    #[test]
+    fn coder0124() {
+        check(Coder0124::new());
+    }
+
+    #[test]
+    fn coder1234() {
+        check(Coder1234::new());
+    }
+
+    #[test]
+    fn coder1248() {
+        check(Coder1248::new());
+    }
+
+    #[test]
+    #[should_panic]
+    fn truncated_data() {
+        let coder = Coder1234::new();
+        let tags = [0xffu8; 2];
+        let data = [0u8; 31];
+        RandomAccessReader::new(coder, &tags, &data);
+    }
+}
@@ -57,6 +57,12 @@ pub(crate) trait RawGroup: Sized + Copy + Debug {
     /// Returns the number of bytes a group with the given tag occupies.
     fn data_len(tag: u8) -> usize;
 
+    /// Returns the tag that `encode()` would produce for group without writing any output.
+    fn tag(group: Self) -> u8;
+
+    /// Returns the tag that `encode_deltas()` would produce for group without writing any output.
+    fn tag_deltas(base: Self, group: Self) -> u8;
+
     /// Skips the group of deltas at input with tag.
     /// Returns the number of input bytes and the sum of the delta values.
     ///
@@ -98,6 +104,30 @@ pub(crate) trait RawGroup: Sized + Copy + Debug {
             .sum()
     }
 
+    /// Returns the index of the first element of group that is greater than or equal to target.
+    #[inline]
+    fn find_ge(group: Self, target: Self::Elem) -> Option<usize> {
+        let mut values = [Self::Elem::zero(); 4];
+        unsafe { Self::store_unaligned(values.as_mut_ptr(), group) };
+        values.iter().position(|v| *v >= target)
+    }
+
+    /// Returns a bitmask with bit `i` set if element `i` of `a` is equal to any element of `b`.
+    #[inline]
+    fn match_any(a: Self, b: Self) -> u32 {
+        let mut avals = [Self::Elem::zero(); 4];
+        let mut bvals = [Self::Elem::zero(); 4];
+        unsafe {
+            Self::store_unaligned(avals.as_mut_ptr(), a);
+            Self::store_unaligned(bvals.as_mut_ptr(), b);
+        }
+        avals
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| bvals.contains(v))
+            .fold(0, |m, (i, _)| m | (1 << i))
+    }
+
     /// Skip 8 groups as deltas.
     /// Returns the number of input bytes read and the sum of the value decoded.
     ///
@@ -189,6 +219,19 @@ pub(crate) mod scalar {
     #[derive(Copy, Clone, Debug)]
     pub(crate) struct ScalarRawGroupImpl<D: CodingDescriptor>([D::Elem; 4]);
 
+    impl<D: CodingDescriptor> ScalarRawGroupImpl<D> {
+        /// Returns the difference between each element of group and the preceding element.
+        #[inline]
+        fn deltas(base: Self, group: Self) -> Self {
+            ScalarRawGroupImpl([
+                group.0[0].wrapping_sub(&base.0[3]),
+                group.0[1].wrapping_sub(&group.0[0]),
+                group.0[2].wrapping_sub(&group.0[1]),
+                group.0[3].wrapping_sub(&group.0[2]),
+            ])
+        }
+    }
+
     impl<D> RawGroup for ScalarRawGroupImpl<D>
     where
         D: CodingDescriptor,
@@ -230,13 +273,7 @@ pub(crate) mod scalar {
 
         #[inline]
         unsafe fn encode_deltas(output: *mut u8, base: Self, group: Self) -> (u8, usize) {
-            let deltas = ScalarRawGroupImpl([
-                group.0[0].wrapping_sub(&base.0[3]),
-                group.0[1].wrapping_sub(&group.0[0]),
-                group.0[2].wrapping_sub(&group.0[1]),
-                group.0[3].wrapping_sub(&group.0[2]),
-            ]);
-            Self::encode(output, deltas)
+            Self::encode(output, Self::deltas(base, group))
         }
 
         #[inline]
@@ -270,6 +307,20 @@ pub(crate) mod scalar {
             D::data_len(tag)
         }
 
+        #[inline]
+        fn tag(group: Self) -> u8 {
+            group
+                .0
+                .iter()
+                .enumerate()
+                .fold(0, |tag, (i, v)| tag | (D::tag_value(*v).0 << (i * 2)))
+        }
+
+        #[inline]
+        fn tag_deltas(base: Self, group: Self) -> u8 {
+            Self::tag(Self::deltas(base, group))
+        }
+
         #[inline]
         unsafe fn skip_deltas(input: *const u8, tag: u8) -> (usize, Self::Elem) {
             let (read, group) = Self::decode(input, tag);
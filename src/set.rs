@@ -0,0 +1,400 @@
+//! Sorted integer set stored as a delta coded stream.
+
+use crate::postings::{self, MergeMode, PostingList, Writer};
+use crate::{Coder, Coder1234, Coder1248, SkipIndex};
+use num_traits::Zero;
+use std::ops::{Bound, RangeBounds};
+
+/// Number of groups in each block of the skip index.
+const INDEX_INTERVAL: usize = 32;
+/// Number of values in each block of the skip index.
+const BLOCK_LEN: usize = INDEX_INTERVAL * 4;
+
+/// An immutable set of integers stored in sorted order as a delta coded stream.
+///
+/// Values are delta coded from zero with `C` and a `SkipIndex` records the position of every
+/// block of 128 values, so membership, `rank()` and `select()` only decode a single block. Set
+/// operations are performed on the encoded streams with the functions in `postings`.
+///
+/// ```
+/// use streamvbyte64::CompressedU32Set;
+///
+/// let set = (0..1000u32).map(|v| v * 3).collect::<CompressedU32Set>();
+/// assert!(set.contains(300));
+/// assert!(!set.contains(301));
+/// assert_eq!(101, set.rank(301));
+/// assert_eq!(Some(303), set.select(101));
+/// assert_eq!(vec![3, 6, 9], set.range(1..10).collect::<Vec<_>>());
+///
+/// let other = (0..100u32).map(|v| v * 2).collect::<CompressedU32Set>();
+/// assert_eq!(34, set.intersection(&other).len());
+/// ```
+#[derive(Clone)]
+pub struct CompressedSet<C: Coder> {
+    coder: C,
+    len: usize,
+    tags: Vec<u8>,
+    data: Vec<u8>,
+    index: SkipIndex<C>,
+}
+
+/// A `CompressedSet` of `u32` values backed by `Coder1234`.
+pub type CompressedU32Set = CompressedSet<Coder1234>;
+/// A `CompressedSet` of `u64` values backed by `Coder1248`.
+pub type CompressedU64Set = CompressedSet<Coder1248>;
+
+impl<C: Coder> CompressedSet<C> {
+    /// Create an empty set.
+    pub fn new(coder: C) -> Self {
+        Self::from_sorted_iter(coder, std::iter::empty())
+    }
+
+    /// Create a set from values in non-decreasing order. Duplicate values are ignored.
+    ///
+    /// # Panics
+    ///
+    /// - If `iter` yields a value less than one that preceded it.
+    pub fn from_sorted_iter<I: IntoIterator<Item = C::Elem>>(coder: C, iter: I) -> Self {
+        let mut writer = Writer::new(&coder);
+        let mut prev = None;
+        for v in iter {
+            match prev {
+                Some(p) if p == v => continue,
+                Some(p) => assert!(p < v, "values must be sorted"),
+                None => {}
+            }
+            writer.push(v);
+            prev = Some(v);
+        }
+        let list = writer.finish();
+        Self::from_list(coder, list)
+    }
+
+    fn from_list(coder: C, list: postings::OwnedPostingList<C::Elem>) -> Self {
+        let (len, tags, data) = list.into_parts();
+        let index = SkipIndex::new_deltas(coder, C::Elem::zero(), &tags, &data, INDEX_INTERVAL);
+        Self {
+            coder,
+            len,
+            tags,
+            data,
+            index,
+        }
+    }
+
+    /// Returns the number of values in the set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the set contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of bytes used by the encoded values and the skip index.
+    pub fn size_in_bytes(&self) -> usize {
+        self.tags.len()
+            + self.data.len()
+            + self.index.num_blocks()
+                * (std::mem::size_of::<usize>() + std::mem::size_of::<C::Elem>())
+    }
+
+    /// Returns the index and value of the first value in the set that is at least `value`.
+    fn find_ge(&self, value: C::Elem) -> Option<(usize, C::Elem)> {
+        if self.is_empty() {
+            return None;
+        }
+        let bases = self.index.bases().unwrap();
+        let block = bases.partition_point(|b| *b < value).saturating_sub(1);
+        let start = block * INDEX_INTERVAL;
+        let end = std::cmp::min(start + INDEX_INTERVAL, self.tags.len());
+        let offset = self.index.offsets()[block];
+        self.coder
+            .find_ge(
+                bases[block],
+                &self.tags[start..end],
+                &self.data[offset..],
+                value,
+            )
+            .map(|(i, v)| (start * 4 + i, v))
+            .filter(|(i, _)| *i < self.len)
+    }
+
+    /// Returns true if the set contains `value`.
+    pub fn contains(&self, value: C::Elem) -> bool {
+        self.find_ge(value).is_some_and(|(_, v)| v == value)
+    }
+
+    /// Returns the number of values in the set that are less than `value`.
+    pub fn rank(&self, value: C::Elem) -> usize {
+        self.find_ge(value).map_or(self.len, |(i, _)| i)
+    }
+
+    /// Returns the value at `index` in sorted order, or `None` if `index >= len()`.
+    pub fn select(&self, index: usize) -> Option<C::Elem> {
+        if index < self.len {
+            Some(self.index.get(&self.tags, &self.data, index))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the smallest value in the set.
+    pub fn first(&self) -> Option<C::Elem> {
+        self.select(0)
+    }
+
+    /// Returns the largest value in the set.
+    pub fn last(&self) -> Option<C::Elem> {
+        self.len.checked_sub(1).and_then(|i| self.select(i))
+    }
+
+    /// Iterate over all values in the set in increasing order.
+    pub fn iter(&self) -> Iter<'_, C> {
+        Iter::new(self, 0, self.len)
+    }
+
+    /// Iterate over values in the set that fall within `range` in increasing order.
+    pub fn range<R: RangeBounds<C::Elem>>(&self, range: R) -> Iter<'_, C> {
+        let start = match range.start_bound() {
+            Bound::Included(v) => self.rank(*v),
+            Bound::Excluded(v) => self.rank(*v) + self.contains(*v) as usize,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(v) => self.rank(*v) + self.contains(*v) as usize,
+            Bound::Excluded(v) => self.rank(*v),
+            Bound::Unbounded => self.len,
+        };
+        Iter::new(self, start, std::cmp::max(start, end))
+    }
+
+    fn as_list(&self) -> PostingList<'_, C::Elem> {
+        PostingList::new(C::Elem::zero(), self.len, &self.tags, &self.data)
+    }
+
+    fn merge(&self, other: &Self, mode: MergeMode) -> Self {
+        let list = postings::merge(&self.coder, &[self.as_list(), other.as_list()], mode);
+        Self::from_list(self.coder, list)
+    }
+
+    /// Returns the values in `self` or `other`.
+    pub fn union(&self, other: &Self) -> Self {
+        self.merge(other, MergeMode::Union)
+    }
+
+    /// Returns the values in both `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        if self.is_empty() || other.is_empty() {
+            return Self::new(self.coder);
+        }
+        let values = postings::intersect(&self.coder, &[self.as_list(), other.as_list()]);
+        Self::from_sorted_iter(self.coder, values)
+    }
+
+    /// Returns the values in `self` that are not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.merge(other, MergeMode::Difference)
+    }
+
+    /// Returns the values in exactly one of `self` and `other`.
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.merge(other, MergeMode::SymmetricDifference)
+    }
+}
+
+impl<C: Coder> FromIterator<C::Elem> for CompressedSet<C> {
+    /// Collect values in non-decreasing order into a set.
+    ///
+    /// # Panics
+    ///
+    /// - If the iterator yields a value less than one that preceded it.
+    fn from_iter<I: IntoIterator<Item = C::Elem>>(iter: I) -> Self {
+        Self::from_sorted_iter(C::new(), iter)
+    }
+}
+
+impl<C: Coder> PartialEq for CompressedSet<C> {
+    fn eq(&self, other: &Self) -> bool {
+        // Encoding is deterministic so equal sets have equal streams.
+        self.len == other.len && self.tags == other.tags && self.data == other.data
+    }
+}
+
+impl<C: Coder> Eq for CompressedSet<C> {}
+
+impl<C: Coder> std::fmt::Debug for CompressedSet<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, C: Coder> IntoIterator for &'a CompressedSet<C> {
+    type Item = C::Elem;
+    type IntoIter = Iter<'a, C>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the values of a `CompressedSet`, decoding one block at a time.
+pub struct Iter<'a, C: Coder> {
+    set: &'a CompressedSet<C>,
+    pos: usize,
+    end: usize,
+    /// Index of the block decoded into `buf`, if any.
+    block: Option<usize>,
+    buf: Vec<C::Elem>,
+}
+
+impl<'a, C: Coder> Iter<'a, C> {
+    fn new(set: &'a CompressedSet<C>, pos: usize, end: usize) -> Self {
+        Self {
+            set,
+            pos,
+            end,
+            block: None,
+            buf: vec![],
+        }
+    }
+}
+
+impl<C: Coder> Iterator for Iter<'_, C> {
+    type Item = C::Elem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.end {
+            return None;
+        }
+        let block = self.pos / BLOCK_LEN;
+        if self.block != Some(block) {
+            self.buf.resize(BLOCK_LEN, C::Elem::zero());
+            self.set
+                .index
+                .decode_block(&self.set.tags, &self.set.data, block, &mut self.buf);
+            self.block = Some(block);
+        }
+        let v = self.buf[self.pos % BLOCK_LEN];
+        self.pos += 1;
+        Some(v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.pos;
+        (len, Some(len))
+    }
+}
+
+impl<C: Coder> ExactSizeIterator for Iter<'_, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coder0124;
+    use num_traits::NumCast;
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    fn generate_set<C: Coder>(rng: &mut StdRng, len: usize, max: u32) -> BTreeSet<C::Elem> {
+        let mut set = BTreeSet::new();
+        while set.len() < len {
+            set.insert(<C::Elem as NumCast>::from(rng.gen_range(0..max)).unwrap());
+        }
+        set
+    }
+
+    fn elem<C: Coder>(v: u32) -> C::Elem {
+        <C::Elem as NumCast>::from(v).unwrap()
+    }
+
+    fn check<C: Coder>(coder: C) {
+        let mut rng = StdRng::from_seed([0x3cu8; 32]);
+        for len in [0, 1, 5, 127, 128, 129, 1000] {
+            let expected = generate_set::<C>(&mut rng, len, 4000);
+            let set = CompressedSet::from_sorted_iter(coder, expected.iter().copied());
+            assert_eq!(expected.len(), set.len());
+            assert_eq!(expected.is_empty(), set.is_empty());
+            assert_eq!(expected.first().copied(), set.first());
+            assert_eq!(expected.last().copied(), set.last());
+            assert_eq!(
+                expected.iter().copied().collect::<Vec<_>>(),
+                set.iter().collect::<Vec<_>>()
+            );
+            assert!(set.size_in_bytes() >= set.tags.len() + set.data.len());
+
+            for v in 0..4001 {
+                let v = elem::<C>(v);
+                assert_eq!(expected.contains(&v), set.contains(v), "value {:?}", v);
+                assert_eq!(expected.range(..v).count(), set.rank(v), "value {:?}", v);
+            }
+            for (i, v) in expected.iter().enumerate() {
+                assert_eq!(Some(*v), set.select(i));
+            }
+            assert_eq!(None, set.select(expected.len()));
+
+            for (start, end) in [(0, 4000), (10, 20), (100, 3000), (500, 400)] {
+                let (start, end) = (elem::<C>(start), elem::<C>(end));
+                let want = expected
+                    .iter()
+                    .copied()
+                    .filter(|v| *v >= start && *v < end)
+                    .collect::<Vec<_>>();
+                assert_eq!(want, set.range(start..end).collect::<Vec<_>>());
+                let want = expected
+                    .iter()
+                    .copied()
+                    .filter(|v| *v > start && *v <= end)
+                    .collect::<Vec<_>>();
+                let range = (Bound::Excluded(start), Bound::Included(end));
+                assert_eq!(want, set.range(range).collect::<Vec<_>>());
+            }
+
+            let other = generate_set::<C>(&mut rng, 300, 4000);
+            let other_set = CompressedSet::from_sorted_iter(coder, other.iter().copied());
+            for (actual, want) in [
+                (set.union(&other_set), &expected | &other),
+                (set.intersection(&other_set), &expected & &other),
+                (set.difference(&other_set), &expected - &other),
+                (set.symmetric_difference(&other_set), &expected ^ &other),
+            ] {
+                assert_eq!(
+                    want.into_iter().collect::<Vec<_>>(),
+                    actual.iter().collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn coder0124() {
+        check(Coder0124::new());
+    }
+
+    #[test]
+    fn coder1234() {
+        check(Coder1234::new());
+    }
+
+    #[test]
+    fn coder1248() {
+        check(Coder1248::new());
+    }
+
+    #[test]
+    fn from_iter() {
+        let set = [1u64, 1, 2, 3, 3, 3, 10]
+            .into_iter()
+            .collect::<CompressedU64Set>();
+        assert_eq!(vec![1, 2, 3, 10], set.iter().collect::<Vec<_>>());
+        assert_eq!(set, set.union(&CompressedU64Set::new(Coder1248::new())));
+        assert_eq!("{1, 2, 3, 10}", format!("{:?}", set));
+    }
+
+    #[test]
+    #[should_panic]
+    fn unsorted() {
+        let _ = [2u32, 1].into_iter().collect::<CompressedU32Set>();
+    }
+}
@@ -0,0 +1,536 @@
+//! Sampled index of positions in an encoded stream for constant time seeking.
+
+use crate::{Coder, CoderId, Error};
+use num_traits::{ops::wrapping::WrappingAdd, NumCast, ToPrimitive, Zero};
+
+/// Version of the serialized index format written by this library.
+const FORMAT_VERSION: u8 = 1;
+/// Length of the serialized index header in bytes.
+const HEADER_LEN: usize = 24;
+/// Header flag set when the index records delta bases.
+const FLAG_DELTA: u8 = 0x1;
+
+/// An index over a tag and data stream that records the position of every `interval`th group.
+///
+/// Random access into a stream otherwise requires computing `data_len()` or `skip_deltas()` over
+/// every tag before the target. A `SkipIndex` stores the data offset at the start of each block of
+/// `interval` groups and, for delta coded streams, the value preceding the block. Seeking then
+/// only touches tags and data within the target block.
+///
+/// The index does not own the stream; the same tags and data used to build the index must be
+/// passed to each method. The index may be serialized with `to_bytes()` and stored alongside the
+/// stream.
+///
+/// ```
+/// use streamvbyte64::{Coder, Coder1234, SkipIndex};
+///
+/// let coder = Coder1234::new();
+/// let values = (0..4096u32).map(|v| v * 5).collect::<Vec<_>>();
+/// let (tag_len, data_len) = Coder1234::max_compressed_bytes(values.len());
+/// let mut tags = vec![0u8; tag_len];
+/// let mut data = vec![0u8; data_len];
+/// let data_len = coder.encode_deltas(0, &values, &mut tags, &mut data);
+/// data.truncate(data_len);
+///
+/// let index = SkipIndex::new_deltas(coder, 0, &tags, &data, 64);
+/// assert_eq!(values[3001], index.get(&tags, &data, 3001));
+/// ```
+#[derive(Clone)]
+pub struct SkipIndex<C: Coder> {
+    coder: C,
+    interval: usize,
+    num_groups: usize,
+    /// Length of the data described by the indexed tags.
+    data_len: usize,
+    /// Data offset of the first group in each block.
+    offsets: Vec<usize>,
+    /// Value preceding the first group in each block for delta coded streams.
+    bases: Option<Vec<C::Elem>>,
+}
+
+impl<C: Coder> SkipIndex<C> {
+    /// Build an index over a stream produced by `Coder::encode()` with an entry every `interval`
+    /// groups.
+    ///
+    /// # Panics
+    ///
+    /// - If `interval == 0` or `interval > u32::MAX`.
+    /// - If `data` is shorter than the length described by `tags`.
+    pub fn new(coder: C, tags: &[u8], data: &[u8], interval: usize) -> Self {
+        assert!(interval > 0 && u32::try_from(interval).is_ok());
+        let mut offsets = Vec::with_capacity(tags.len().div_ceil(interval));
+        let mut offset = 0;
+        for block in tags.chunks(interval) {
+            offsets.push(offset);
+            offset += coder.data_len(block);
+        }
+        assert!(offset <= data.len());
+        Self {
+            coder,
+            interval,
+            num_groups: tags.len(),
+            data_len: offset,
+            offsets,
+            bases: None,
+        }
+    }
+
+    /// Build an index over a stream produced by `Coder::encode_deltas()` from `initial` with an
+    /// entry every `interval` groups.
+    ///
+    /// # Panics
+    ///
+    /// - If `interval == 0` or `interval > u32::MAX`.
+    /// - If `data` is shorter than the length described by `tags`.
+    pub fn new_deltas(
+        coder: C,
+        initial: C::Elem,
+        tags: &[u8],
+        data: &[u8],
+        interval: usize,
+    ) -> Self {
+        assert!(interval > 0 && u32::try_from(interval).is_ok());
+        let num_blocks = tags.len().div_ceil(interval);
+        let mut offsets = Vec::with_capacity(num_blocks);
+        let mut bases = Vec::with_capacity(num_blocks);
+        let mut offset = 0;
+        let mut base = initial;
+        for block in tags.chunks(interval) {
+            offsets.push(offset);
+            bases.push(base);
+            let (read, sum) = coder.skip_deltas(block, &data[offset..]);
+            offset += read;
+            base = base.wrapping_add(&sum);
+        }
+        assert!(offset <= data.len());
+        Self {
+            coder,
+            interval,
+            num_groups: tags.len(),
+            data_len: offset,
+            offsets,
+            bases: Some(bases),
+        }
+    }
+
+    /// Build an index over a stream produced by `Coder::encode_deltas_with_restarts()` from
+    /// `initial` with `restart_interval`.
+    ///
+    /// The index has an entry at every restart point, so `interval()` is `restart_interval / 4`
+    /// groups and every block may be decoded from `initial`. Unlike `new_deltas()` this only needs
+    /// to read the tags.
+    ///
+    /// # Panics
+    ///
+    /// - If `restart_interval` is zero or not a multiple of 4.
+    /// - If `restart_interval / 4 > u32::MAX`.
+    /// - If `data` is shorter than the length described by `tags`.
+    pub fn new_restarts(
+        coder: C,
+        initial: C::Elem,
+        restart_interval: usize,
+        tags: &[u8],
+        data: &[u8],
+    ) -> Self {
+        assert!(restart_interval > 0 && restart_interval.is_multiple_of(4));
+        let mut index = Self::new(coder, tags, data, restart_interval / 4);
+        index.bases = Some(vec![initial; index.num_blocks()]);
+        index
+    }
+
+    /// Returns the number of groups in each block.
+    pub fn interval(&self) -> usize {
+        self.interval
+    }
+
+    /// Returns the number of groups in the indexed stream.
+    pub fn num_groups(&self) -> usize {
+        self.num_groups
+    }
+
+    /// Returns the length of the data stream described by the indexed tags.
+    pub fn data_len(&self) -> usize {
+        self.data_len
+    }
+
+    /// Returns the number of blocks in the indexed stream.
+    pub fn num_blocks(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Returns true if the index was built over a delta coded stream.
+    pub fn is_delta(&self) -> bool {
+        self.bases.is_some()
+    }
+
+    /// Returns the value preceding the first group of each block for delta coded streams.
+    pub(crate) fn bases(&self) -> Option<&[C::Elem]> {
+        self.bases.as_deref()
+    }
+
+    /// Returns the data offset of the first group of each block.
+    pub(crate) fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
+    /// Returns the data offset of `group` and the value that precedes it.
+    ///
+    /// The value is only meaningful for delta coded streams and is zero otherwise.
+    ///
+    /// # Panics
+    ///
+    /// - If `group > num_groups()`.
+    /// - If `data.len() < data_len()`.
+    pub fn seek(&self, tags: &[u8], data: &[u8], group: usize) -> (usize, C::Elem) {
+        assert!(group <= self.num_groups);
+        assert!(data.len() >= self.data_len);
+        let block = std::cmp::min(group / self.interval, self.num_blocks().saturating_sub(1));
+        let start = block * self.interval;
+        let offset = self.offsets.get(block).copied().unwrap_or(0);
+        match &self.bases {
+            Some(bases) => {
+                let base = bases.get(block).copied().unwrap_or_else(C::Elem::zero);
+                let (read, sum) = self.coder.skip_deltas(&tags[start..group], &data[offset..]);
+                (offset + read, base.wrapping_add(&sum))
+            }
+            None => (
+                offset + self.coder.data_len(&tags[start..group]),
+                C::Elem::zero(),
+            ),
+        }
+    }
+
+    /// Returns the value at `index` in the stream, decoding only the group that contains it.
+    ///
+    /// # Panics
+    ///
+    /// - If `index >= num_groups() * 4`.
+    /// - If `data.len() < data_len()`.
+    pub fn get(&self, tags: &[u8], data: &[u8], index: usize) -> C::Elem {
+        let group = index / 4;
+        assert!(group < self.num_groups);
+        let (offset, base) = self.seek(tags, data, group);
+        let mut values = [C::Elem::zero(); 4];
+        let tag = &tags[group..(group + 1)];
+        if self.is_delta() {
+            self.coder
+                .decode_deltas(base, tag, &data[offset..], &mut values);
+        } else {
+            self.coder.decode(tag, &data[offset..], &mut values);
+        }
+        values[index % 4]
+    }
+
+    /// Decode all of the groups in `block` to the start of `values`.
+    ///
+    /// Returns the number of values written, which is `interval() * 4` for all but the last block.
+    ///
+    /// # Panics
+    ///
+    /// - If `block >= num_blocks()`.
+    /// - If `values` cannot hold all of the values in the block.
+    /// - If `data.len() < data_len()`.
+    pub fn decode_block(
+        &self,
+        tags: &[u8],
+        data: &[u8],
+        block: usize,
+        values: &mut [C::Elem],
+    ) -> usize {
+        assert!(data.len() >= self.data_len);
+        let start = block * self.interval;
+        let end = std::cmp::min(start + self.interval, self.num_groups);
+        let offset = self.offsets[block];
+        let len = (end - start) * 4;
+        let block_tags = &tags[start..end];
+        match &self.bases {
+            Some(bases) => self.coder.decode_deltas(
+                bases[block],
+                block_tags,
+                &data[offset..],
+                &mut values[..len],
+            ),
+            None => self
+                .coder
+                .decode(block_tags, &data[offset..], &mut values[..len]),
+        };
+        len
+    }
+
+    /// Serialize the index.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let entry_len = if self.is_delta() { 16 } else { 8 };
+        let mut out = Vec::with_capacity(HEADER_LEN + self.num_blocks() * entry_len);
+        out.push(FORMAT_VERSION);
+        out.push(C::ID as u8);
+        out.push(if self.is_delta() { FLAG_DELTA } else { 0 });
+        out.push(0);
+        // Constructors ensure that interval fits.
+        out.extend_from_slice(&(self.interval as u32).to_le_bytes());
+        out.extend_from_slice(&(self.num_groups as u64).to_le_bytes());
+        out.extend_from_slice(&(self.data_len as u64).to_le_bytes());
+        for offset in self.offsets.iter() {
+            out.extend_from_slice(&(*offset as u64).to_le_bytes());
+        }
+        if let Some(bases) = &self.bases {
+            for base in bases.iter() {
+                out.extend_from_slice(&base.to_u64().unwrap().to_le_bytes());
+            }
+        }
+        out
+    }
+
+    /// Parse an index serialized by `to_bytes()` for use with `coder`.
+    pub fn from_bytes(coder: C, bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < HEADER_LEN {
+            return Err(Error::Truncated);
+        }
+        if bytes[0] != FORMAT_VERSION {
+            return Err(Error::UnsupportedVersion(bytes[0]));
+        }
+        let actual = CoderId::try_from(bytes[1])?;
+        if actual != C::ID {
+            return Err(Error::CoderMismatch {
+                expected: C::ID,
+                actual,
+            });
+        }
+        let flags = bytes[2];
+        if flags & !FLAG_DELTA != 0 {
+            return Err(Error::InvalidHeader("flags"));
+        }
+        if bytes[3] != 0 {
+            return Err(Error::InvalidHeader("reserved"));
+        }
+        let interval = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        if interval == 0 {
+            return Err(Error::InvalidHeader("interval"));
+        }
+        let read_usize = |offset: usize, field: &'static str| {
+            let value = u64::from_le_bytes(bytes[offset..(offset + 8)].try_into().unwrap());
+            usize::try_from(value).map_err(|_| Error::InvalidHeader(field))
+        };
+        let num_groups = read_usize(8, "num_groups")?;
+        let data_len = read_usize(16, "data_len")?;
+        let num_blocks = num_groups.div_ceil(interval);
+        let entry_len = if flags & FLAG_DELTA != 0 { 16 } else { 8 };
+        let body_len = num_blocks
+            .checked_mul(entry_len)
+            .ok_or(Error::InvalidHeader("num_groups"))?;
+        let body = &bytes[HEADER_LEN..];
+        if body.len() < body_len {
+            return Err(Error::Truncated);
+        }
+        if body.len() > body_len {
+            return Err(Error::InvalidHeader("trailing bytes"));
+        }
+
+        let mut entries = body
+            .chunks_exact(8)
+            .map(|e| u64::from_le_bytes(e.try_into().unwrap()));
+        let offsets = entries
+            .by_ref()
+            .take(num_blocks)
+            .map(|o| usize::try_from(o).map_err(|_| Error::InvalidHeader("offset")))
+            .collect::<Result<Vec<_>, _>>()?;
+        if num_blocks == 0 && data_len != 0 {
+            return Err(Error::InvalidHeader("data_len"));
+        }
+        // Blocks must be contiguous, start at zero, and fit the data their groups could encode.
+        let max_block_len = interval.saturating_mul(4 * std::mem::size_of::<C::Elem>());
+        let ends = offsets.iter().skip(1).chain(std::iter::once(&data_len));
+        if offsets.first().is_some_and(|o| *o != 0)
+            || offsets
+                .iter()
+                .zip(ends)
+                .any(|(start, end)| start > end || end - start > max_block_len)
+        {
+            return Err(Error::InvalidHeader("offset"));
+        }
+        let bases = if flags & FLAG_DELTA != 0 {
+            Some(
+                entries
+                    .map(|b| <C::Elem as NumCast>::from(b).ok_or(Error::InvalidHeader("base")))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )
+        } else {
+            None
+        };
+        Ok(Self {
+            coder,
+            interval,
+            num_groups,
+            data_len,
+            offsets,
+            bases,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{generate_array, generate_cumulative_array};
+    use crate::{Coder0124, Coder1234, Coder1248};
+
+    fn encode<C: Coder>(
+        coder: &C,
+        initial: Option<C::Elem>,
+        values: &[C::Elem],
+    ) -> (Vec<u8>, Vec<u8>) {
+        let (tag_len, data_len) = C::max_compressed_bytes(values.len());
+        let mut tags = vec![0u8; tag_len];
+        let mut data = vec![0u8; data_len];
+        let data_len = match initial {
+            Some(i) => coder.encode_deltas(i, values, &mut tags, &mut data),
+            None => coder.encode(values, &mut tags, &mut data),
+        };
+        data.truncate(data_len);
+        (tags, data)
+    }
+
+    fn check_index<C: Coder>(index: &SkipIndex<C>, tags: &[u8], data: &[u8], values: &[C::Elem]) {
+        for (i, v) in values.iter().enumerate() {
+            assert_eq!(*v, index.get(tags, data, i), "index {}", i);
+        }
+        let mut decoded = vec![C::Elem::zero(); index.interval() * 4];
+        let mut start = 0;
+        for block in 0..index.num_blocks() {
+            let n = index.decode_block(tags, data, block, &mut decoded);
+            assert_eq!(&values[start..(start + n)], &decoded[..n]);
+            start += n;
+        }
+        assert_eq!(values.len(), start);
+        assert_eq!(data.len(), index.seek(tags, data, index.num_groups()).0);
+    }
+
+    fn check<C: Coder>(coder: C) {
+        for len in [0, 4, 400, 4096] {
+            for interval in [1, 3, 64] {
+                let values = generate_array::<C::Elem>(len, std::mem::size_of::<C::Elem>());
+                let (tags, data) = encode(&coder, None, &values);
+                let index = SkipIndex::new(coder, &tags, &data, interval);
+                check_index(&index, &tags, &data, &values);
+                let index = SkipIndex::from_bytes(coder, &index.to_bytes()).unwrap();
+                check_index(&index, &tags, &data, &values);
+
+                let initial = <C::Elem as num_traits::One>::one();
+                let values = generate_cumulative_array::<C::Elem>(len, 2, initial);
+                let (tags, data) = encode(&coder, Some(initial), &values);
+                let index = SkipIndex::new_deltas(coder, initial, &tags, &data, interval);
+                check_index(&index, &tags, &data, &values);
+                let index = SkipIndex::from_bytes(coder, &index.to_bytes()).unwrap();
+                assert!(index.is_delta());
+                check_index(&index, &tags, &data, &values);
+            }
+        }
+    }
+
+    #[test]
+    fn restarts() {
+        let coder = Coder1234::new();
+        // Lengths where the last run is full and where it is partial.
+        for (len, restart_interval) in [(1024, 64), (1000, 64), (1004, 256), (12, 16)] {
+            let values = generate_cumulative_array::<u32>(len, 2, 7);
+            let (tag_len, data_len) = Coder1234::max_compressed_bytes(values.len());
+            let mut tags = vec![0u8; tag_len];
+            let mut data = vec![0u8; data_len];
+            let data_len = coder.encode_deltas_with_restarts(
+                7,
+                restart_interval,
+                &values,
+                &mut tags,
+                &mut data,
+            );
+            data.truncate(data_len);
+            let index = SkipIndex::new_restarts(coder, 7, restart_interval, &tags, &data);
+            assert_eq!(restart_interval / 4, index.interval());
+            assert_eq!(len.div_ceil(restart_interval), index.num_blocks());
+            check_index(&index, &tags, &data, &values);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn restarts_invalid_interval() {
+        SkipIndex::new_restarts(Coder1234::new(), 0, 30, &[0u8; 8], &[0u8; 8]);
+    }
+
+    #[test]
+    fn coder0124() {
+        check(Coder0124::new());
+    }
+
+    #[test]
+    fn coder1234() {
+        check(Coder1234::new());
+    }
+
+    #[test]
+    fn coder1248() {
+        check(Coder1248::new());
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        let coder = Coder1234::new();
+        let values = generate_cumulative_array::<u32>(400, 2, 0);
+        let (tags, data) = encode(&coder, Some(0), &values);
+        let bytes = SkipIndex::new_deltas(coder, 0, &tags, &data, 8).to_bytes();
+
+        for len in 0..bytes.len() {
+            assert_eq!(
+                Some(Error::Truncated),
+                SkipIndex::from_bytes(coder, &bytes[..len]).err()
+            );
+        }
+        assert!(matches!(
+            SkipIndex::from_bytes(Coder1248::new(), &bytes),
+            Err(Error::CoderMismatch { .. })
+        ));
+        let mut invalid = bytes.clone();
+        invalid[4..8].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(
+            Some(Error::InvalidHeader("interval")),
+            SkipIndex::from_bytes(coder, &invalid).err()
+        );
+        let mut invalid = bytes.clone();
+        invalid[0] = 2;
+        assert_eq!(
+            Some(Error::UnsupportedVersion(2)),
+            SkipIndex::from_bytes(coder, &invalid).err()
+        );
+        let mut invalid = bytes.clone();
+        invalid.push(0);
+        assert!(SkipIndex::from_bytes(coder, &invalid).is_err());
+
+        // Offsets must be increasing, start at zero and lie within the data.
+        for (entry, offset) in [(0, 1), (2, 0), (3, data.len() as u64 + 1)] {
+            let mut invalid = bytes.clone();
+            let start = HEADER_LEN + entry * 8;
+            invalid[start..(start + 8)].copy_from_slice(&offset.to_le_bytes());
+            assert_eq!(
+                Some(Error::InvalidHeader("offset")),
+                SkipIndex::from_bytes(coder, &invalid).err(),
+                "entry {} offset {}",
+                entry,
+                offset
+            );
+        }
+        let mut invalid = bytes.clone();
+        invalid[16..24].copy_from_slice(&(data.len() as u64 * 2).to_le_bytes());
+        assert_eq!(
+            Some(Error::InvalidHeader("offset")),
+            SkipIndex::from_bytes(coder, &invalid).err()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn deltas_truncated_data() {
+        let coder = Coder1234::new();
+        let values = generate_cumulative_array::<u32>(64, 4, 0);
+        let (tags, data) = encode(&coder, Some(0), &values);
+        SkipIndex::new_deltas(coder, 0, &tags, &data[..(data.len() - 1)], 64);
+    }
+}
@@ -0,0 +1,388 @@
+use super::{parse_frame, parse_header, trailer_remainder, FRAME_LEN, HEADER_LEN};
+use crate::{Coder, Error};
+use num_traits::Zero;
+use std::io::{self, Read};
+
+/// Reads framed chunks written by `StreamEncoder` from a `Read`er and decodes them in batches.
+///
+/// Each chunk is decoded with `Coder::decode()` or `Coder::decode_deltas()` into an internal
+/// buffer that is reused for the next chunk. Chunks are limited to `MAX_CHUNK_GROUPS` groups, so
+/// memory use is bounded regardless of the length of the stream.
+///
+/// Values may be read with `next_batch()` or by iterating. The iterator ends at the first error,
+/// which may be retrieved with `take_error()`. An error may leave the reader in the middle of a
+/// chunk, so after any error the decoder is poisoned: `next_batch()` returns an error with the same
+/// kind and message and iteration yields no more values.
+///
+/// ```
+/// use streamvbyte64::{Coder, Coder1234, StreamDecoder, StreamEncoder};
+///
+/// let values = (0..1000u32).map(|v| v * 3).collect::<Vec<_>>();
+/// let mut encoder = StreamEncoder::new_deltas(Coder1234::new(), 0, Vec::new()).unwrap();
+/// encoder.write_all(&values).unwrap();
+/// let encoded = encoder.finish().unwrap();
+///
+/// let mut decoder = StreamDecoder::new(Coder1234::new(), encoded.as_slice()).unwrap();
+/// assert_eq!(values, decoder.by_ref().collect::<Vec<_>>());
+/// assert!(decoder.take_error().is_none());
+/// ```
+pub struct StreamDecoder<C: Coder, R: Read> {
+    coder: C,
+    reader: R,
+    delta: bool,
+    initial: C::Elem,
+    base: C::Elem,
+    tags: Vec<u8>,
+    data: Vec<u8>,
+    values: Vec<C::Elem>,
+    pos: usize,
+    count: u64,
+    finished: bool,
+    error: Option<io::Error>,
+    /// Kind and message of the first error returned by `fill()`.
+    poisoned: Option<(io::ErrorKind, String)>,
+}
+
+impl<C: Coder, R: Read> StreamDecoder<C, R> {
+    /// Create a new decoder reading from `reader`.
+    ///
+    /// The stream header is read immediately and must have been written with the same `Coder`.
+    pub fn new(coder: C, mut reader: R) -> io::Result<Self> {
+        let mut header = [0u8; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+        let (delta, initial) = parse_header::<C>(&header)?;
+        Ok(Self {
+            coder,
+            reader,
+            delta,
+            initial,
+            base: initial,
+            tags: vec![],
+            data: vec![],
+            values: vec![],
+            pos: 0,
+            count: 0,
+            finished: false,
+            error: None,
+            poisoned: None,
+        })
+    }
+
+    /// Returns true if values in the stream are delta coded.
+    pub fn is_delta(&self) -> bool {
+        self.delta
+    }
+
+    /// Returns the initial value for delta coding.
+    pub fn initial(&self) -> C::Elem {
+        self.initial
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Decode values into `values`, returning the number of values written.
+    ///
+    /// Returns fewer than `values.len()` values only at the end of the stream, after which it
+    /// returns 0, or when an error occurs after some values were written. The error is then
+    /// returned by the next call.
+    pub fn next_batch(&mut self, values: &mut [C::Elem]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < values.len() {
+            if self.pos == self.values.len() {
+                match self.fill() {
+                    Ok(true) => {}
+                    Ok(false) => break,
+                    // The decoder is poisoned, so the next call reports the error.
+                    Err(_) if written > 0 => break,
+                    Err(e) => return Err(e),
+                }
+            }
+            let n = std::cmp::min(values.len() - written, self.values.len() - self.pos);
+            values[written..(written + n)].copy_from_slice(&self.values[self.pos..(self.pos + n)]);
+            self.pos += n;
+            written += n;
+        }
+        Ok(written)
+    }
+
+    /// Returns and clears the error that ended iteration, if any.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Read and decode the next chunk into `values`, poisoning the decoder on error.
+    /// Returns false if there are no more values in the stream.
+    fn fill(&mut self) -> io::Result<bool> {
+        if let Some((kind, message)) = &self.poisoned {
+            return Err(io::Error::new(*kind, message.clone()));
+        }
+        self.fill_chunk().inspect_err(|e| {
+            self.poisoned = Some((e.kind(), e.to_string()));
+            self.values.clear();
+            self.pos = 0;
+        })
+    }
+
+    /// Read and decode the next chunk into `values`.
+    /// Returns false if there are no more values in the stream.
+    fn fill_chunk(&mut self) -> io::Result<bool> {
+        if self.finished {
+            return Ok(false);
+        }
+
+        let mut frame = [0u8; FRAME_LEN];
+        self.reader.read_exact(&mut frame)?;
+        let (num_groups, data_len) = parse_frame::<C>(&frame)?;
+        if num_groups == 0 {
+            return self.fill_trailer(frame[4..].try_into().unwrap());
+        }
+
+        self.tags.resize(num_groups, 0);
+        self.reader.read_exact(&mut self.tags)?;
+        self.data.resize(data_len, 0);
+        self.reader.read_exact(&mut self.data)?;
+        self.decode_chunk(num_groups * 4)?;
+        self.count += self.values.len() as u64;
+        Ok(true)
+    }
+
+    /// Read the end of the stream, including the final partial group.
+    fn fill_trailer(&mut self, count_prefix: [u8; 4]) -> io::Result<bool> {
+        self.finished = true;
+        let mut count = [0u8; 8];
+        count[..4].copy_from_slice(&count_prefix);
+        self.reader.read_exact(&mut count[4..])?;
+        let count = u64::from_le_bytes(count);
+        let remainder = trailer_remainder(count, self.count)?;
+        if remainder == 0 {
+            self.values.clear();
+            self.pos = 0;
+            return Ok(false);
+        }
+
+        self.tags.resize(1, 0);
+        self.reader.read_exact(&mut self.tags)?;
+        self.data.resize(self.coder.data_len(&self.tags), 0);
+        self.reader.read_exact(&mut self.data)?;
+        self.decode_chunk(4)?;
+        self.values.truncate(remainder);
+        self.count = count;
+        Ok(true)
+    }
+
+    /// Decode `len` values from `tags` and `data` into `values`.
+    fn decode_chunk(&mut self, len: usize) -> Result<(), Error> {
+        let expected = self.coder.data_len(&self.tags);
+        if expected != self.data.len() {
+            return Err(Error::DataLenMismatch {
+                expected,
+                actual: self.data.len(),
+            });
+        }
+        self.values.resize(len, C::Elem::zero());
+        self.pos = 0;
+        if self.delta {
+            self.coder
+                .decode_deltas(self.base, &self.tags, &self.data, &mut self.values);
+            self.base = *self.values.last().unwrap();
+        } else {
+            self.coder.decode(&self.tags, &self.data, &mut self.values);
+        }
+        Ok(())
+    }
+}
+
+impl<C: Coder, R: Read> Iterator for StreamDecoder<C, R> {
+    type Item = C::Elem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos == self.values.len() {
+            if self.poisoned.is_some() {
+                return None;
+            }
+            match self.fill() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(e) => {
+                    self.error = Some(e);
+                    return None;
+                }
+            }
+        }
+        let v = self.values[self.pos];
+        self.pos += 1;
+        Some(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MAX_CHUNK_GROUPS;
+    use crate::tests::{generate_array, generate_cumulative_array};
+    use crate::{Coder0124, Coder1234, Coder1248, StreamEncoder};
+
+    fn round_trip<C: Coder>(coder: C) {
+        for len in [0, 1, 3, 4, 5, 63, 64, 65, 1000, 4097] {
+            for batch_len in [4, 64, 4096] {
+                let values = generate_array::<C::Elem>(len, std::mem::size_of::<C::Elem>());
+                let mut encoder = StreamEncoder::new(coder, vec![])
+                    .unwrap()
+                    .with_batch_len(batch_len);
+                encoder.write_all(&values).unwrap();
+                let bytes = encoder.finish().unwrap();
+                let mut decoder = StreamDecoder::new(coder, bytes.as_slice()).unwrap();
+                assert!(!decoder.is_delta());
+                assert_eq!(values, decoder.by_ref().collect::<Vec<_>>());
+                assert!(decoder.take_error().is_none());
+
+                let initial = <C::Elem as num_traits::NumCast>::from(11).unwrap();
+                let values = generate_cumulative_array::<C::Elem>(len, 2, initial);
+                let mut encoder = StreamEncoder::new_deltas(coder, initial, vec![])
+                    .unwrap()
+                    .with_batch_len(batch_len);
+                encoder.write_all(&values).unwrap();
+                let bytes = encoder.finish().unwrap();
+                let mut decoder = StreamDecoder::new(coder, bytes.as_slice()).unwrap();
+                assert!(decoder.is_delta());
+                assert_eq!(initial, decoder.initial());
+                let mut decoded = vec![];
+                let mut batch = [C::Elem::zero(); 7];
+                loop {
+                    let n = decoder.next_batch(&mut batch).unwrap();
+                    decoded.extend_from_slice(&batch[..n]);
+                    if n < batch.len() {
+                        break;
+                    }
+                }
+                assert_eq!(0, decoder.next_batch(&mut batch).unwrap());
+                assert_eq!(values, decoded);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_coder0124() {
+        round_trip(Coder0124::new());
+    }
+
+    #[test]
+    fn round_trip_coder1234() {
+        round_trip(Coder1234::new());
+    }
+
+    #[test]
+    fn round_trip_coder1248() {
+        round_trip(Coder1248::new());
+    }
+
+    fn encode_stream(len: usize) -> Vec<u8> {
+        let mut encoder = StreamEncoder::new(Coder1234::new(), vec![])
+            .unwrap()
+            .with_batch_len(64);
+        encoder.write_all(&generate_array::<u32>(len, 4)).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn coder_mismatch() {
+        let bytes = encode_stream(10);
+        let err = StreamDecoder::new(Coder0124::new(), bytes.as_slice())
+            .err()
+            .unwrap();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = encode_stream(1001);
+        for len in HEADER_LEN..bytes.len() {
+            let mut decoder = StreamDecoder::new(Coder1234::new(), &bytes[..len]).unwrap();
+            decoder.by_ref().for_each(drop);
+            let err = decoder.take_error().unwrap();
+            assert_eq!(io::ErrorKind::UnexpectedEof, err.kind(), "len {}", len);
+        }
+    }
+
+    /// Fails a single read at `fail_at`, then continues reading from `bytes`.
+    struct FlakyReader<'a> {
+        bytes: &'a [u8],
+        pos: usize,
+        fail_at: Option<usize>,
+    }
+
+    impl Read for FlakyReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut end = std::cmp::min(self.bytes.len(), self.pos + buf.len());
+            if let Some(fail_at) = self.fail_at {
+                if self.pos == fail_at {
+                    self.fail_at = None;
+                    return Err(io::Error::other("flaky"));
+                }
+                end = std::cmp::min(end, fail_at);
+            }
+            let n = end - self.pos;
+            buf[..n].copy_from_slice(&self.bytes[self.pos..end]);
+            self.pos = end;
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn poisoned_by_error() {
+        let bytes = encode_stream(1001);
+        // Fail in the middle of the tags of the first chunk.
+        let reader = FlakyReader {
+            bytes: &bytes,
+            pos: 0,
+            fail_at: Some(HEADER_LEN + FRAME_LEN + 5),
+        };
+        let mut decoder = StreamDecoder::new(Coder1234::new(), reader).unwrap();
+        let mut batch = [0u32; 16];
+        let err = decoder.next_batch(&mut batch).err().unwrap();
+        assert_eq!(io::ErrorKind::Other, err.kind());
+        // The reader would now succeed but resuming mid-chunk would decode garbage.
+        let err = decoder.next_batch(&mut batch).err().unwrap();
+        assert_eq!(io::ErrorKind::Other, err.kind());
+        assert_eq!("flaky", err.to_string());
+        assert_eq!(None, decoder.next());
+        assert!(decoder.take_error().is_none());
+    }
+
+    #[test]
+    fn partial_batch_before_error() {
+        let values = generate_array::<u32>(1001, 4);
+        let mut encoder = StreamEncoder::new(Coder1234::new(), vec![])
+            .unwrap()
+            .with_batch_len(64);
+        encoder.write_all(&values).unwrap();
+        let bytes = encoder.finish().unwrap();
+        // Fail partway through the stream so that earlier chunks decode.
+        let reader = FlakyReader {
+            bytes: &bytes,
+            pos: 0,
+            fail_at: Some(bytes.len() / 2),
+        };
+        let mut decoder = StreamDecoder::new(Coder1234::new(), reader).unwrap();
+        let mut batch = vec![0u32; values.len()];
+        let n = decoder.next_batch(&mut batch).unwrap();
+        assert!(n > 0 && n < values.len(), "n {}", n);
+        assert_eq!(&values[..n], &batch[..n]);
+        let err = decoder.next_batch(&mut batch).err().unwrap();
+        assert_eq!("flaky", err.to_string());
+    }
+
+    #[test]
+    fn oversized_chunk() {
+        let mut bytes = encode_stream(100);
+        bytes[HEADER_LEN..(HEADER_LEN + 4)]
+            .copy_from_slice(&(MAX_CHUNK_GROUPS as u32 + 1).to_le_bytes());
+        let mut decoder = StreamDecoder::new(Coder1234::new(), bytes.as_slice()).unwrap();
+        let mut batch = [0u32; 4];
+        let err = decoder.next_batch(&mut batch).err().unwrap();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
@@ -0,0 +1,338 @@
+use super::{FLAG_DELTA, FORMAT_VERSION, FRAME_LEN, HEADER_LEN, MAX_CHUNK_GROUPS};
+use crate::Coder;
+use num_traits::{ToPrimitive, Zero};
+use std::io::{self, Write};
+
+/// Encodes values as they are produced and writes framed chunks to a `Write`r.
+///
+/// Values are buffered until a batch of `batch_len()` values is available, which is then encoded
+/// with `Coder::encode()` or `Coder::encode_deltas()` and written as one chunk. Call `finish()`
+/// to write the trailing partial group and total count; a stream that is dropped without calling
+/// `finish()` is incomplete. See the module documentation for the format.
+///
+/// Wrap the writer in a `BufWriter` if it is expensive to write small amounts of data.
+///
+/// If writing a chunk fails part of it may already have been written, so the encoder is poisoned
+/// and every later call that would write a chunk returns an error.
+///
+/// ```
+/// use streamvbyte64::{Coder, Coder1234, StreamEncoder};
+///
+/// let mut encoder = StreamEncoder::new_deltas(Coder1234::new(), 0, Vec::new()).unwrap();
+/// for v in 0..1000u32 {
+///     encoder.write(v * 3).unwrap();
+/// }
+/// let encoded = encoder.finish().unwrap();
+/// ```
+pub struct StreamEncoder<C: Coder, W: Write> {
+    coder: C,
+    writer: W,
+    delta: bool,
+    base: C::Elem,
+    batch_len: usize,
+    values: Vec<C::Elem>,
+    tags: Vec<u8>,
+    data: Vec<u8>,
+    count: u64,
+    poisoned: bool,
+}
+
+impl<C: Coder, W: Write> StreamEncoder<C, W> {
+    /// Default number of values buffered before a chunk is encoded.
+    pub const DEFAULT_BATCH_LEN: usize = 4096;
+
+    /// Create a new encoder that writes values to `writer`.
+    ///
+    /// The stream header is written immediately.
+    pub fn new(coder: C, writer: W) -> io::Result<Self> {
+        Self::with_header(coder, writer, false, C::Elem::zero())
+    }
+
+    /// Create a new encoder that writes values to `writer` as deltas starting from `initial`.
+    ///
+    /// The stream header is written immediately.
+    pub fn new_deltas(coder: C, initial: C::Elem, writer: W) -> io::Result<Self> {
+        Self::with_header(coder, writer, true, initial)
+    }
+
+    fn with_header(coder: C, mut writer: W, delta: bool, initial: C::Elem) -> io::Result<Self> {
+        let mut header = [0u8; HEADER_LEN];
+        header[0] = FORMAT_VERSION;
+        header[1] = C::ID as u8;
+        header[2] = if delta { FLAG_DELTA } else { 0 };
+        header[4..].copy_from_slice(&initial.to_u64().unwrap().to_le_bytes());
+        writer.write_all(&header)?;
+        Ok(Self {
+            coder,
+            writer,
+            delta,
+            base: initial,
+            batch_len: Self::DEFAULT_BATCH_LEN,
+            values: Vec::with_capacity(Self::DEFAULT_BATCH_LEN),
+            tags: vec![],
+            data: vec![],
+            count: 0,
+            poisoned: false,
+        })
+    }
+
+    /// Set the number of values buffered before a chunk is encoded.
+    ///
+    /// # Panics
+    ///
+    /// - If `batch_len` is zero, not a multiple of 4, or larger than `MAX_CHUNK_GROUPS * 4`.
+    pub fn with_batch_len(mut self, batch_len: usize) -> Self {
+        assert!(batch_len > 0 && batch_len.is_multiple_of(4));
+        assert!(batch_len <= MAX_CHUNK_GROUPS * 4);
+        self.batch_len = batch_len;
+        self
+    }
+
+    /// Returns the number of values buffered before a chunk is encoded.
+    pub fn batch_len(&self) -> usize {
+        self.batch_len
+    }
+
+    /// Returns the number of values written so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Append a single value to the stream.
+    pub fn write(&mut self, value: C::Elem) -> io::Result<()> {
+        self.check_poisoned()?;
+        self.values.push(value);
+        self.count += 1;
+        if self.values.len() >= self.batch_len {
+            self.write_chunk()?;
+        }
+        Ok(())
+    }
+
+    /// Append all of `values` to the stream.
+    pub fn write_all(&mut self, mut values: &[C::Elem]) -> io::Result<()> {
+        self.check_poisoned()?;
+        while !values.is_empty() {
+            let n = std::cmp::min(self.batch_len - self.values.len(), values.len());
+            self.values.extend_from_slice(&values[..n]);
+            self.count += n as u64;
+            values = &values[n..];
+            if self.values.len() >= self.batch_len {
+                self.write_chunk()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Encode all whole groups that have been buffered and flush the underlying writer.
+    ///
+    /// Up to 3 values may remain buffered until more values are written or the stream finishes.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.write_chunk()?;
+        self.writer.flush()
+    }
+
+    /// Write all buffered values and the end of the stream, returning the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_chunk()?;
+        debug_assert!(self.values.len() < 4);
+
+        let mut trailer = [0u8; 12];
+        trailer[4..].copy_from_slice(&self.count.to_le_bytes());
+        self.writer.write_all(&trailer)?;
+        if !self.values.is_empty() {
+            // Padding deltas are zero so that they occupy the smallest encoding.
+            let pad = if self.delta {
+                *self.values.last().unwrap()
+            } else {
+                C::Elem::zero()
+            };
+            self.values.resize(4, pad);
+            let (len, _) = self.encode_values();
+            self.writer.write_all(&self.tags[..1])?;
+            self.writer.write_all(&self.data[..len])?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Encode the whole groups in `values` to `tags` and `data`.
+    ///
+    /// Returns the data length and the base for the next chunk; `base` is not updated so that it
+    /// only advances once the chunk has been written.
+    fn encode_values(&mut self) -> (usize, C::Elem) {
+        let num_values = self.values.len() & !3;
+        let (tag_len, data_len) = C::max_compressed_bytes(num_values);
+        self.tags.resize(tag_len, 0);
+        self.data.resize(data_len, 0);
+        let values = &self.values[..num_values];
+        if self.delta {
+            let len = self
+                .coder
+                .encode_deltas(self.base, values, &mut self.tags, &mut self.data);
+            (len, *values.last().unwrap())
+        } else {
+            (
+                self.coder.encode(values, &mut self.tags, &mut self.data),
+                self.base,
+            )
+        }
+    }
+
+    /// Returns an error if an earlier write failed.
+    fn check_poisoned(&self) -> io::Result<()> {
+        if self.poisoned {
+            return Err(io::Error::other(
+                "StreamEncoder poisoned by an earlier write error",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Write all whole groups in `values` as a single chunk.
+    fn write_chunk(&mut self) -> io::Result<()> {
+        self.check_poisoned()?;
+        let num_groups = self.values.len() / 4;
+        if num_groups == 0 {
+            return Ok(());
+        }
+        let (len, base) = self.encode_values();
+        let mut frame = [0u8; FRAME_LEN];
+        frame[..4].copy_from_slice(&(num_groups as u32).to_le_bytes());
+        frame[4..].copy_from_slice(&(len as u32).to_le_bytes());
+        // A failed write may leave a partial chunk in the output.
+        self.poisoned = true;
+        self.writer.write_all(&frame)?;
+        self.writer.write_all(&self.tags[..num_groups])?;
+        self.writer.write_all(&self.data[..len])?;
+        self.poisoned = false;
+        self.base = base;
+        self.values.drain(..(num_groups * 4));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::HEADER_LEN;
+    use crate::tests::generate_cumulative_array;
+    use crate::{Coder1234, CoderId};
+
+    fn read_u32(bytes: &[u8]) -> usize {
+        u32::from_le_bytes(bytes[..4].try_into().unwrap()) as usize
+    }
+
+    #[test]
+    fn framing() {
+        let coder = Coder1234::new();
+        let values = generate_cumulative_array::<u32>(1003, 2, 9);
+        let mut encoder = StreamEncoder::new_deltas(coder, 9, vec![])
+            .unwrap()
+            .with_batch_len(64);
+        encoder.write_all(&values[..500]).unwrap();
+        for v in &values[500..] {
+            encoder.write(*v).unwrap();
+        }
+        assert_eq!(values.len() as u64, encoder.count());
+        let bytes = encoder.finish().unwrap();
+
+        assert_eq!(
+            &[FORMAT_VERSION, CoderId::Coder1234 as u8, FLAG_DELTA, 0],
+            &bytes[..4]
+        );
+        assert_eq!(9u64.to_le_bytes(), bytes[4..HEADER_LEN]);
+
+        // Walk the chunks and decode them by hand.
+        let mut decoded = vec![];
+        let mut base = 9;
+        let mut rest = &bytes[HEADER_LEN..];
+        loop {
+            let num_groups = read_u32(rest);
+            if num_groups == 0 {
+                break;
+            }
+            assert!(num_groups <= 16);
+            let data_len = read_u32(&rest[4..]);
+            let (tags, data) = rest[FRAME_LEN..].split_at(num_groups);
+            let mut chunk = vec![0u32; num_groups * 4];
+            assert_eq!(
+                data_len,
+                coder.decode_deltas(base, tags, &data[..data_len], &mut chunk)
+            );
+            base = *chunk.last().unwrap();
+            decoded.extend_from_slice(&chunk);
+            rest = &rest[(FRAME_LEN + num_groups + data_len)..];
+        }
+        assert_eq!(
+            values.len() as u64,
+            u64::from_le_bytes(rest[4..12].try_into().unwrap())
+        );
+        let (tag, data) = rest[12..].split_at(1);
+        assert_eq!(coder.data_len(tag), data.len());
+        let mut last = [0u32; 4];
+        coder.decode_deltas(base, tag, data, &mut last);
+        decoded.extend_from_slice(&last[..3]);
+        assert_eq!(values, decoded);
+    }
+
+    /// Accepts up to `limit` bytes, then fails every write.
+    struct LimitedWriter {
+        written: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Write for LimitedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = std::cmp::min(buf.len(), self.limit - self.written.len());
+            if n == 0 {
+                return Err(io::Error::other("full"));
+            }
+            self.written.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn poisoned_by_write_error() {
+        let values = generate_cumulative_array::<u32>(64, 2, 0);
+        let writer = LimitedWriter {
+            written: vec![],
+            limit: HEADER_LEN + FRAME_LEN + 4,
+        };
+        let mut encoder = StreamEncoder::new_deltas(Coder1234::new(), 0, writer)
+            .unwrap()
+            .with_batch_len(32);
+        assert!(encoder.write_all(&values[..32]).is_err());
+        assert_eq!(0, encoder.base);
+        assert_eq!(HEADER_LEN + FRAME_LEN + 4, encoder.get_ref().written.len());
+
+        encoder.writer.limit = usize::MAX;
+        assert!(encoder.flush().is_err());
+        // Values are rejected without being buffered or counted.
+        let (count, buffered) = (encoder.count(), encoder.values.len());
+        assert!(encoder.write(values[32]).is_err());
+        assert!(encoder.write_all(&values[32..]).is_err());
+        assert_eq!(count, encoder.count());
+        assert_eq!(buffered, encoder.values.len());
+        assert!(encoder.finish().is_err());
+    }
+
+    #[test]
+    fn empty() {
+        let bytes = StreamEncoder::new(Coder1234::new(), vec![])
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(HEADER_LEN + 12, bytes.len());
+    }
+}
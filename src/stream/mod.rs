@@ -0,0 +1,92 @@
+//! Framed stream format for coding values incrementally through `std::io` readers and writers.
+//!
+//! A stream begins with a 12 byte header:
+//!
+//! | Bytes | Contents                                   |
+//! | ----- | ------------------------------------------ |
+//! | 1     | format version                             |
+//! | 1     | `CoderId`                                  |
+//! | 1     | flags; bit 0 is set if values are deltas   |
+//! | 1     | reserved, always 0                         |
+//! | 8     | little-endian initial value for deltas     |
+//!
+//! The header is followed by any number of chunks of whole groups, each framed as a little-endian
+//! `u32` group count, a little-endian `u32` data length, then the tag and data streams. The stream
+//! ends with a chunk whose group count is 0, followed by the total number of values as a
+//! little-endian `u64`. If that count is not a multiple of 4 the final partial group follows as a
+//! single tag byte and its data; the padding values in that group are discarded when decoding.
+//!
+//! When delta coding the delta base is carried between chunks, so chunks may only be decoded in
+//! order.
+
+mod decoder;
+mod encoder;
+mod push;
+
+pub use decoder::StreamDecoder;
+pub use encoder::StreamEncoder;
+pub use push::PushDecoder;
+
+use crate::{Coder, CoderId, Error};
+use num_traits::NumCast;
+
+/// Version of the stream format written by this library.
+const FORMAT_VERSION: u8 = 1;
+/// Length of the stream header in bytes.
+const HEADER_LEN: usize = 12;
+/// Header flag set when values are delta coded from `initial`.
+const FLAG_DELTA: u8 = 0x1;
+/// Length of each chunk frame (group count and data length) in bytes.
+const FRAME_LEN: usize = 8;
+/// Largest number of groups that may appear in a single chunk.
+///
+/// This bounds the memory used to decode a stream regardless of who produced it.
+pub const MAX_CHUNK_GROUPS: usize = 1 << 16;
+
+/// Parse a stream header written for `C`, returning the delta flag and initial value.
+fn parse_header<C: Coder>(header: &[u8; HEADER_LEN]) -> Result<(bool, C::Elem), Error> {
+    if header[0] != FORMAT_VERSION {
+        return Err(Error::UnsupportedVersion(header[0]));
+    }
+    let actual = CoderId::try_from(header[1])?;
+    if actual != C::ID {
+        return Err(Error::CoderMismatch {
+            expected: C::ID,
+            actual,
+        });
+    }
+    if header[2] & !FLAG_DELTA != 0 {
+        return Err(Error::InvalidHeader("flags"));
+    }
+    if header[3] != 0 {
+        return Err(Error::InvalidHeader("reserved"));
+    }
+    let initial = <C::Elem as NumCast>::from(u64::from_le_bytes(header[4..].try_into().unwrap()))
+        .ok_or(Error::InvalidHeader("initial"))?;
+    Ok((header[2] & FLAG_DELTA != 0, initial))
+}
+
+/// Parse a chunk frame written for `C`, returning the group count and data length.
+///
+/// A group count of zero marks the trailer, in which case the data length field holds the low
+/// bytes of the total value count and is not validated.
+fn parse_frame<C: Coder>(frame: &[u8; FRAME_LEN]) -> Result<(usize, usize), Error> {
+    let num_groups = u32::from_le_bytes(frame[..4].try_into().unwrap()) as usize;
+    let data_len = u32::from_le_bytes(frame[4..].try_into().unwrap()) as usize;
+    if num_groups > MAX_CHUNK_GROUPS {
+        return Err(Error::InvalidHeader("group count"));
+    }
+    if data_len > C::max_compressed_bytes(num_groups * 4).1 && num_groups > 0 {
+        return Err(Error::InvalidHeader("data length"));
+    }
+    Ok((num_groups, data_len))
+}
+
+/// Returns the number of values in the final partial group given the total `count` from the
+/// trailer and the number of values `decoded` from whole groups.
+fn trailer_remainder(count: u64, decoded: u64) -> Result<usize, Error> {
+    if count < decoded || count - decoded >= 4 {
+        return Err(Error::InvalidHeader("count"));
+    }
+    Ok((count - decoded) as usize)
+}
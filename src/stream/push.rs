@@ -0,0 +1,395 @@
+use super::{parse_frame, parse_header, trailer_remainder, FRAME_LEN, HEADER_LEN};
+use crate::{Coder, Error};
+use num_traits::Zero;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    /// Waiting for the stream header.
+    Header,
+    /// Waiting for the next chunk frame.
+    Frame,
+    /// Accumulating the tags of a chunk with `data_len` bytes of data.
+    Tags { data_len: usize },
+    /// Decoding groups starting with the tag at index `next`.
+    Data { next: usize },
+    /// Waiting for the high bytes of the value count in the trailer.
+    Count,
+    /// Waiting for the tag of the final partial group.
+    TailTag,
+    /// The stream is complete.
+    Done,
+}
+
+/// Decodes a stream written by `StreamEncoder` from input that arrives in arbitrary fragments.
+///
+/// Input may be split anywhere, including mid-group or between the tag and data streams of a
+/// chunk. Each call to `push()` emits every value whose tag and data are complete. Between calls
+/// the decoder keeps only the tags of the current chunk, the bytes of a partially received group
+/// or frame, and the delta base.
+///
+/// After `push()` returns an error the position within the stream is unknown, so the decoder is
+/// poisoned and every later call to `push()` or `finish()` returns the same error.
+///
+/// ```
+/// use streamvbyte64::{Coder, Coder1234, PushDecoder, StreamEncoder};
+///
+/// let values = (0..1000u32).map(|v| v * 3).collect::<Vec<_>>();
+/// let mut encoder = StreamEncoder::new_deltas(Coder1234::new(), 0, Vec::new()).unwrap();
+/// encoder.write_all(&values).unwrap();
+/// let encoded = encoder.finish().unwrap();
+///
+/// let mut decoder = PushDecoder::new(Coder1234::new());
+/// let mut decoded = Vec::new();
+/// for fragment in encoded.chunks(7) {
+///     decoder.push(fragment, &mut decoded).unwrap();
+/// }
+/// assert!(decoder.is_finished());
+/// assert_eq!(values, decoded);
+/// ```
+pub struct PushDecoder<C: Coder> {
+    coder: C,
+    state: State,
+    delta: bool,
+    base: C::Elem,
+    /// Bytes of an incomplete fixed length field or group.
+    pending: Vec<u8>,
+    /// Tags for the current chunk.
+    tags: Vec<u8>,
+    /// Number of values in the final partial group, once known.
+    remainder: usize,
+    count: u64,
+    /// The first error returned by `push()`.
+    error: Option<Error>,
+}
+
+impl<C: Coder> PushDecoder<C> {
+    /// Create a new decoder that expects to receive the stream header first.
+    pub fn new(coder: C) -> Self {
+        Self {
+            coder,
+            state: State::Header,
+            delta: false,
+            base: C::Elem::zero(),
+            pending: vec![],
+            tags: vec![],
+            remainder: 0,
+            count: 0,
+            error: None,
+        }
+    }
+
+    /// Returns true once the end of the stream has been decoded.
+    pub fn is_finished(&self) -> bool {
+        self.state == State::Done
+    }
+
+    /// Returns an error if the end of the stream has not been decoded.
+    pub fn finish(&self) -> Result<(), Error> {
+        if let Some(e) = &self.error {
+            Err(e.clone())
+        } else if self.is_finished() {
+            Ok(())
+        } else {
+            Err(Error::Truncated)
+        }
+    }
+
+    /// Consume `input`, appending every value that can be fully decoded to `values`.
+    ///
+    /// Returns the number of values appended. Any input after the end of the stream is an error.
+    /// Values decoded before an error are still appended to `values`.
+    pub fn push(&mut self, input: &[u8], values: &mut Vec<C::Elem>) -> Result<usize, Error> {
+        if let Some(e) = &self.error {
+            return Err(e.clone());
+        }
+        self.push_input(input, values).inspect_err(|e| {
+            self.error = Some(e.clone());
+        })
+    }
+
+    /// Implementation of `push()` that does not record errors.
+    fn push_input(&mut self, mut input: &[u8], values: &mut Vec<C::Elem>) -> Result<usize, Error> {
+        let start = values.len();
+        // Groups with no data may be decoded even if there is no input left.
+        while !input.is_empty() || matches!(self.state, State::Data { .. }) {
+            match self.state {
+                State::Header => {
+                    if let Some(header) = self.take_fixed::<HEADER_LEN>(&mut input) {
+                        let (delta, initial) = parse_header::<C>(&header)?;
+                        self.delta = delta;
+                        self.base = initial;
+                        self.state = State::Frame;
+                    }
+                }
+                State::Frame => {
+                    if let Some(frame) = self.take_fixed::<FRAME_LEN>(&mut input) {
+                        let (num_groups, data_len) = parse_frame::<C>(&frame)?;
+                        self.tags.clear();
+                        if num_groups == 0 {
+                            // The data length field holds the low bytes of the count.
+                            self.pending.extend_from_slice(&frame[4..]);
+                            self.state = State::Count;
+                        } else {
+                            self.tags.reserve(num_groups);
+                            self.remainder = num_groups;
+                            self.state = State::Tags { data_len };
+                        }
+                    }
+                }
+                State::Tags { data_len } => {
+                    let n = std::cmp::min(self.remainder - self.tags.len(), input.len());
+                    self.tags.extend_from_slice(&input[..n]);
+                    input = &input[n..];
+                    if self.tags.len() == self.remainder {
+                        let expected = self.coder.data_len(&self.tags);
+                        if expected != data_len {
+                            return Err(Error::DataLenMismatch {
+                                expected,
+                                actual: data_len,
+                            });
+                        }
+                        self.remainder = 0;
+                        self.state = State::Data { next: 0 };
+                    }
+                }
+                State::Data { next } => {
+                    let next = self.decode_groups(next, &mut input, values);
+                    if next < self.tags.len() {
+                        self.state = State::Data { next };
+                        if input.is_empty() {
+                            break;
+                        }
+                    } else if self.remainder > 0 {
+                        // Discard the padding in the final partial group.
+                        values.truncate(values.len() - (4 - self.remainder));
+                        self.state = State::Done;
+                    } else {
+                        self.state = State::Frame;
+                    }
+                }
+                State::Count => {
+                    if let Some(count) = self.take_fixed::<8>(&mut input) {
+                        let count = u64::from_le_bytes(count);
+                        self.remainder = trailer_remainder(count, self.count)?;
+                        self.count = count;
+                        self.state = if self.remainder > 0 {
+                            State::TailTag
+                        } else {
+                            State::Done
+                        };
+                    }
+                }
+                State::TailTag => {
+                    self.tags.clear();
+                    self.tags.push(input[0]);
+                    input = &input[1..];
+                    self.state = State::Data { next: 0 };
+                }
+                State::Done => return Err(Error::InvalidHeader("trailing input")),
+            }
+        }
+        Ok(values.len() - start)
+    }
+
+    /// Accumulate bytes from `input` in `pending` until `N` bytes are available.
+    fn take_fixed<const N: usize>(&mut self, input: &mut &[u8]) -> Option<[u8; N]> {
+        let n = std::cmp::min(N - self.pending.len(), input.len());
+        self.pending.extend_from_slice(&input[..n]);
+        *input = &input[n..];
+        if self.pending.len() == N {
+            let field = self.pending[..].try_into().unwrap();
+            self.pending.clear();
+            Some(field)
+        } else {
+            None
+        }
+    }
+
+    /// Decode as many groups as possible starting at tag index `next` from `pending` and `input`.
+    /// Returns the index of the next group to decode.
+    fn decode_groups(
+        &mut self,
+        mut next: usize,
+        input: &mut &[u8],
+        values: &mut Vec<C::Elem>,
+    ) -> usize {
+        while next < self.tags.len() {
+            let group_len = self.coder.data_len(&self.tags[next..(next + 1)]);
+            if group_len > 0 && input.is_empty() {
+                break;
+            }
+            if !self.pending.is_empty() || input.len() < group_len {
+                // Complete a group that spans calls to push().
+                let n = std::cmp::min(group_len - self.pending.len(), input.len());
+                self.pending.extend_from_slice(&input[..n]);
+                *input = &input[n..];
+                if self.pending.len() < group_len {
+                    break;
+                }
+                let tag = [self.tags[next]];
+                let data = std::mem::take(&mut self.pending);
+                self.decode(&tag, &data, values);
+                self.pending = data;
+                self.pending.clear();
+                next += 1;
+                continue;
+            }
+
+            // Decode every whole group available in input in a single call.
+            let mut end = next;
+            let mut len = 0;
+            while end < self.tags.len() {
+                let l = self.coder.data_len(&self.tags[end..(end + 1)]);
+                if len + l > input.len() {
+                    break;
+                }
+                len += l;
+                end += 1;
+            }
+            let tags = std::mem::take(&mut self.tags);
+            self.decode(&tags[next..end], &input[..len], values);
+            self.tags = tags;
+            *input = &input[len..];
+            next = end;
+        }
+        next
+    }
+
+    /// Decode all of `tags` from `data`, appending to `values`.
+    fn decode(&mut self, tags: &[u8], data: &[u8], values: &mut Vec<C::Elem>) {
+        if self.delta {
+            self.coder
+                .decode_deltas_to_vec(self.base, tags, data, values);
+            self.base = *values.last().unwrap();
+        } else {
+            self.coder.decode_to_vec(tags, data, values);
+        }
+        self.count += (tags.len() * 4) as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{generate_array, generate_cumulative_array};
+    use crate::{Coder0124, Coder1234, Coder1248, StreamEncoder};
+
+    fn encode<C: Coder>(coder: C, initial: Option<C::Elem>, values: &[C::Elem]) -> Vec<u8> {
+        let mut encoder = match initial {
+            Some(i) => StreamEncoder::new_deltas(coder, i, vec![]),
+            None => StreamEncoder::new(coder, vec![]),
+        }
+        .unwrap()
+        .with_batch_len(16);
+        encoder.write_all(values).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Split every stream at every offset, then again at every offset of the second half.
+    fn split_everywhere<C: Coder>(coder: C, initial: Option<C::Elem>, values: &[C::Elem]) {
+        let bytes = encode(coder, initial, values);
+        for i in 0..=bytes.len() {
+            for j in i..=bytes.len() {
+                let mut decoder = PushDecoder::new(coder);
+                let mut decoded = vec![];
+                let mut n = decoder.push(&bytes[..i], &mut decoded).unwrap();
+                n += decoder.push(&bytes[i..j], &mut decoded).unwrap();
+                n += decoder.push(&bytes[j..], &mut decoded).unwrap();
+                assert!(decoder.is_finished(), "split at {} {}", i, j);
+                assert_eq!(Ok(()), decoder.finish());
+                assert_eq!(values.len(), n);
+                assert_eq!(values, decoded, "split at {} {}", i, j);
+            }
+        }
+    }
+
+    fn check<C: Coder>(coder: C) {
+        for len in [0, 3, 4, 37] {
+            let values = generate_array::<C::Elem>(len, std::mem::size_of::<C::Elem>());
+            split_everywhere(coder, None, &values);
+            let initial = C::Elem::zero();
+            let values = generate_cumulative_array::<C::Elem>(len, 2, initial);
+            split_everywhere(coder, Some(initial), &values);
+        }
+    }
+
+    #[test]
+    fn split_coder0124() {
+        check(Coder0124::new());
+    }
+
+    #[test]
+    fn split_coder1234() {
+        check(Coder1234::new());
+    }
+
+    #[test]
+    fn split_coder1248() {
+        check(Coder1248::new());
+    }
+
+    #[test]
+    fn byte_at_a_time() {
+        let coder = Coder1234::new();
+        let values = generate_cumulative_array::<u32>(1001, 3, 1);
+        let bytes = encode(coder, Some(1), &values);
+        let mut decoder = PushDecoder::new(coder);
+        let mut decoded = vec![];
+        for (i, b) in bytes.iter().enumerate() {
+            decoder.push(std::slice::from_ref(b), &mut decoded).unwrap();
+            assert_eq!(i + 1 == bytes.len(), decoder.is_finished());
+        }
+        assert_eq!(values, decoded);
+    }
+
+    #[test]
+    fn empty_groups() {
+        let coder = Coder0124::new();
+        for len in [5, 8] {
+            let values = vec![0u32; len];
+            split_everywhere(coder, None, &values);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let coder = Coder1234::new();
+        let bytes = encode(coder, None, &generate_array::<u32>(10, 4));
+        let mut decoder = PushDecoder::new(coder);
+        let mut decoded = vec![];
+        decoder
+            .push(&bytes[..(bytes.len() - 1)], &mut decoded)
+            .unwrap();
+        assert_eq!(Err(Error::Truncated), decoder.finish());
+        decoder
+            .push(&bytes[(bytes.len() - 1)..], &mut decoded)
+            .unwrap();
+        assert_eq!(Ok(()), decoder.finish());
+        assert!(decoder.push(&[0], &mut decoded).is_err());
+
+        let mut decoder = PushDecoder::new(Coder0124::new());
+        assert!(matches!(
+            decoder.push(&bytes, &mut decoded),
+            Err(Error::CoderMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn poisoned_by_error() {
+        let coder = Coder1234::new();
+        let mut bytes = encode(coder, None, &generate_array::<u32>(10, 4));
+        // Corrupt the data length of the first chunk.
+        bytes[HEADER_LEN + 4] -= 1;
+        let mut decoder = PushDecoder::new(coder);
+        let mut decoded = vec![];
+        let split = HEADER_LEN + FRAME_LEN + 2;
+        let err = decoder.push(&bytes[..split], &mut decoded).unwrap_err();
+        assert!(matches!(err, Error::DataLenMismatch { .. }));
+        assert_eq!(
+            Err(err.clone()),
+            decoder.push(&bytes[split..], &mut decoded)
+        );
+        assert_eq!(Err(err), decoder.finish());
+        assert!(decoded.is_empty());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::raw_group::RawGroup;
-use num_traits::{ops::wrapping::WrappingAdd, One, PrimInt, Zero};
+use num_traits::{ops::wrapping::WrappingAdd, One, PrimInt, Saturating, Zero};
 use rand::distributions::Uniform;
 use rand::prelude::*;
 use std::iter::Iterator;
@@ -355,6 +355,80 @@ where
     }
 }
 
+pub(crate) fn test_tag<TGroup: RawGroup>() {
+    unsafe {
+        for test in TagIter::<TGroup::Elem>::new(
+            TGroup::TAG_LEN,
+            TagIter::<TGroup::Elem>::mask_patterns([0x1a, 0x1b, 0x1c, 0x1d]),
+        ) {
+            let group = TGroup::load_unaligned(test.group.as_ptr());
+            assert_eq!(test.tag, TGroup::tag(group));
+        }
+
+        for test in TagIter::<TGroup::Elem>::new(TGroup::TAG_LEN, [smol_mask::<TGroup>(); 4]) {
+            let base = TGroup::Elem::one();
+            let integrated = integrate_delta(base, test.group);
+            let group = TGroup::load_unaligned(integrated.as_ptr());
+            assert_eq!(test.tag, TGroup::tag_deltas(TGroup::set1(base), group));
+        }
+    }
+}
+
+pub(crate) fn test_find_ge<TGroup: RawGroup>() {
+    for test in TagIter::<TGroup::Elem>::new(
+        TGroup::TAG_LEN,
+        TagIter::<TGroup::Elem>::mask_patterns([0x1a, 0x1b, 0x1c, 0x1d]),
+    ) {
+        let group = unsafe { TGroup::load_unaligned(test.group.as_ptr()) };
+        for target in test.group.iter().flat_map(|v| {
+            [
+                (*v).saturating_sub(TGroup::Elem::one()),
+                *v,
+                (*v).saturating_add(TGroup::Elem::one()),
+            ]
+        }) {
+            assert_eq!(
+                test.group.iter().position(|v| *v >= target),
+                TGroup::find_ge(group, target),
+                "group={:?} target={:?}",
+                test.group,
+                target
+            );
+        }
+    }
+}
+
+pub(crate) fn test_match_any<TGroup: RawGroup>() {
+    for test in TagIter::<TGroup::Elem>::new(
+        TGroup::TAG_LEN,
+        TagIter::<TGroup::Elem>::mask_patterns([0x1a, 0x1b, 0x1c, 0x1d]),
+    ) {
+        let a = unsafe { TGroup::load_unaligned(test.group.as_ptr()) };
+        for rotate in 0..4 {
+            let mut other = test.group;
+            other.rotate_left(rotate);
+            // Replace some values so that only a subset of lanes match.
+            for v in other.iter_mut().take(rotate) {
+                *v = v.wrapping_add(&TGroup::Elem::one());
+            }
+            let b = unsafe { TGroup::load_unaligned(other.as_ptr()) };
+            let expected = test
+                .group
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| other.contains(v))
+                .fold(0, |m, (i, _)| m | (1 << i));
+            assert_eq!(
+                expected,
+                TGroup::match_any(a, b),
+                "a={:?} b={:?}",
+                test.group,
+                other
+            );
+        }
+    }
+}
+
 /// Define `group_suite` module with conformance tests for `RawGroup` implementations.
 /// Invoke this inside the module defining your `RawGroupImpl`.
 macro_rules! raw_group_test_suite {
@@ -397,6 +471,21 @@ macro_rules! raw_group_test_suite {
             fn skip_deltas8() {
                 crate::tests::test_skip_deltas8::<RawGroupImpl, RawGroupImpl>();
             }
+
+            #[test]
+            fn tag() {
+                crate::tests::test_tag::<RawGroupImpl>();
+            }
+
+            #[test]
+            fn find_ge() {
+                crate::tests::test_find_ge::<RawGroupImpl>();
+            }
+
+            #[test]
+            fn match_any() {
+                crate::tests::test_match_any::<RawGroupImpl>();
+            }
         }
     };
 }
@@ -568,6 +657,353 @@ macro_rules! coder_test_suite {
                 }
             }
 
+            #[test]
+            fn encode_decode_deltas_with_restarts() {
+                let coder = $group_impl::new();
+                let initial = 1;
+                for max_bytes in $coding_descriptor::TAG_LEN {
+                    let expected = generate_cumulative_array(4000, max_bytes, initial);
+                    for restart_interval in [4, 64, 1024, 8192] {
+                        let (tbytes, dbytes) = $group_impl::max_compressed_bytes(expected.len());
+                        let mut tags = vec![0u8; tbytes];
+                        let mut data = vec![0u8; dbytes];
+                        let data_len = coder.encode_deltas_with_restarts(initial, restart_interval, &expected, &mut tags, &mut data);
+                        data.truncate(data_len);
+
+                        let mut actual = vec![0; expected.len()];
+                        assert_eq!(data_len, coder.decode_deltas_with_restarts(initial, restart_interval, &tags, &data, &mut actual));
+                        assert_eq!(expected, actual, "max_bytes={} restart_interval={}", max_bytes, restart_interval);
+
+                        // Each run decodes independently.
+                        let mut offset = 0;
+                        for (run_tags, run) in tags.chunks(restart_interval / 4).zip(expected.chunks(restart_interval)) {
+                            let mut actual = vec![0; run.len()];
+                            offset += coder.decode_deltas(initial, run_tags, &data[offset..], &mut actual);
+                            assert_eq!(run, &actual[..]);
+                        }
+                        assert_eq!(data_len, offset);
+                    }
+                }
+            }
+
+            #[test]
+            fn find_ge() {
+                let coder = $group_impl::new();
+                let initial = 7;
+                // Keep deltas small enough that the running sum does not wrap.
+                let expected = generate_cumulative_array(1001 & !3, 2, initial);
+                let (tbytes, dbytes) = $group_impl::max_compressed_bytes(expected.len());
+                let mut tags = vec![0u8; tbytes];
+                let mut data = vec![0u8; dbytes];
+                let data_len = coder.encode_deltas(initial, &expected, &mut tags, &mut data);
+                data.truncate(data_len);
+
+                let mut targets = vec![0, initial, *expected.last().unwrap() + 1];
+                targets.extend(expected.iter().flat_map(|v| [*v - 1, *v, *v + 1]));
+                for target in targets {
+                    let found = expected
+                        .iter()
+                        .position(|v| *v >= target)
+                        .map(|i| (i, expected[i]));
+                    assert_eq!(found, coder.find_ge(initial, &tags, &data, target), "target={}", target);
+                }
+            }
+
+            #[test]
+            fn intersect_deltas() {
+                let coder = $group_impl::new();
+                let initial = 7;
+                let expected = generate_cumulative_array(4096, 2, initial + 1);
+                let (tbytes, dbytes) = $group_impl::max_compressed_bytes(expected.len());
+                let mut tags = vec![0u8; tbytes];
+                let mut data = vec![0u8; dbytes];
+                let data_len = coder.encode_deltas(initial, &expected, &mut tags, &mut data);
+                data.truncate(data_len);
+
+                // Test against dense and sparse samples of the stream mixed with missing values.
+                for stride in [1, 3, 64, 1000] {
+                    let values = expected
+                        .iter()
+                        .step_by(stride)
+                        .flat_map(|v| [*v - 1, *v])
+                        .filter(|v| *v > initial)
+                        .collect::<std::collections::BTreeSet<_>>()
+                        .into_iter()
+                        .collect::<Vec<_>>();
+                    let mut want = values.clone();
+                    want.retain(|v| expected.binary_search(v).is_ok());
+                    let mut actual = vec![];
+                    coder.intersect_deltas(initial, &tags, &data, &values, &mut actual);
+                    assert_eq!(want, actual, "stride={}", stride);
+                }
+
+                // Alternate dense runs and long gaps so the gallop step carried between values
+                // both overshoots and undershoots.
+                let values = expected
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| i % 700 < 9)
+                    .map(|(_, v)| *v)
+                    .collect::<Vec<_>>();
+                let mut actual = vec![];
+                coder.intersect_deltas(initial, &tags, &data, &values, &mut actual);
+                assert_eq!(values, actual);
+            }
+
+            /// Split `bytes` into segments of `len` bytes, with an empty segment after each one.
+            fn segments(bytes: &[u8], len: usize) -> Vec<&[u8]> {
+                bytes.chunks(len).flat_map(|c| [c, &[][..]]).collect()
+            }
+
+            #[test]
+            fn decode_segments() {
+                let coder = $group_impl::new();
+                for max_bytes in $coding_descriptor::TAG_LEN {
+                    let initial = 1;
+                    let expected = generate_cumulative_array(4096, max_bytes, initial);
+                    let (tbytes, dbytes) = $group_impl::max_compressed_bytes(expected.len());
+                    let mut tags = vec![0u8; tbytes];
+                    let mut data = vec![0u8; dbytes];
+                    let data_len = coder.encode_deltas(initial, &expected, &mut tags, &mut data);
+                    data.truncate(data_len);
+                    let mut deltas = vec![0; expected.len()];
+                    coder.decode(&tags, &data, &mut deltas);
+
+                    for (tag_seg_len, data_seg_len) in [(1, 1), (3, 17), (64, 5), (1000, 1000), (4096, 4096 * 8)] {
+                        let tag_segs = segments(&tags, tag_seg_len);
+                        let data_segs = segments(&data, data_seg_len);
+                        let mut actual = vec![0; expected.len()];
+                        assert_eq!(data_len, coder.decode_segments(&tag_segs, &data_segs, &mut actual));
+                        assert_eq!(deltas, actual, "max_bytes={} segments=({}, {})", max_bytes, tag_seg_len, data_seg_len);
+                        let mut actual = vec![0; expected.len()];
+                        assert_eq!(data_len, coder.decode_deltas_segments(initial, &tag_segs, &data_segs, &mut actual));
+                        assert_eq!(expected, actual, "max_bytes={} segments=({}, {})", max_bytes, tag_seg_len, data_seg_len);
+                        assert_eq!(
+                            (data_len, expected.last().unwrap().wrapping_sub(initial)),
+                            coder.skip_deltas_segments(&tag_segs, &data_segs)
+                        );
+                    }
+                }
+            }
+
+            #[test]
+            #[should_panic]
+            fn decode_segments_short_tags() {
+                let coder = $group_impl::new();
+                let tags = [0u8; 3];
+                let data = [0u8; 64];
+                let mut values = vec![0; 16];
+                coder.decode_segments(&[&tags[..2], &tags[2..]], &[&data], &mut values);
+            }
+
+            #[test]
+            fn decode_value() {
+                let coder = $group_impl::new();
+                for max_bytes in $coding_descriptor::TAG_LEN {
+                    let expected = generate_array(4096, max_bytes);
+                    let (tbytes, dbytes) = $group_impl::max_compressed_bytes(expected.len());
+                    let mut tags = vec![0u8; tbytes];
+                    let mut data = vec![0u8; dbytes];
+                    let data_len = coder.encode(&expected, &mut tags, &mut data);
+                    data.truncate(data_len);
+
+                    let mut offset = 0;
+                    for (group, tag) in tags.iter().enumerate() {
+                        for i in 0..4 {
+                            assert_eq!(
+                                expected[group * 4 + i],
+                                coder.decode_value(*tag, &data[offset..], i),
+                                "max_bytes={} group={} index={}", max_bytes, group, i
+                            );
+                        }
+                        offset += coder.data_len(std::slice::from_ref(tag));
+                    }
+                }
+            }
+
+            #[test]
+            fn decode_range() {
+                let coder = $group_impl::new();
+                for max_bytes in $coding_descriptor::TAG_LEN {
+                    let initial = 1;
+                    let expected = generate_cumulative_array(1024, max_bytes, initial);
+                    let (tbytes, dbytes) = $group_impl::max_compressed_bytes(expected.len());
+                    let mut tags = vec![0u8; tbytes];
+                    let mut data = vec![0u8; dbytes];
+                    let data_len = coder.encode_deltas(initial, &expected, &mut tags, &mut data);
+                    data.truncate(data_len);
+                    let mut deltas = vec![0; expected.len()];
+                    coder.decode(&tags, &data, &mut deltas);
+
+                    for start in [0, 1, 2, 3, 4, 5, 511, 1020, 1023, 1024] {
+                        for len in [0, 1, 2, 3, 4, 5, 8, 9, 300] {
+                            let end = std::cmp::min(start + len, expected.len());
+                            let mut actual = vec![0; end - start + 1];
+                            coder.decode_range(&tags, &data, start..end, &mut actual);
+                            assert_eq!(&deltas[start..end], &actual[..(end - start)], "max_bytes={} range={}..{}", max_bytes, start, end);
+                            assert_eq!(0, actual[end - start]);
+                            let mut actual = vec![0; end - start];
+                            coder.decode_deltas_range(initial, &tags, &data, start..end, &mut actual);
+                            assert_eq!(&expected[start..end], &actual[..], "max_bytes={} range={}..{}", max_bytes, start, end);
+                        }
+                    }
+                }
+            }
+
+            #[test]
+            fn decode_uninit() {
+                let coder = $group_impl::new();
+                for max_bytes in $coding_descriptor::TAG_LEN {
+                    let initial = 1;
+                    let expected = generate_cumulative_array(4100, max_bytes, initial);
+                    let (tbytes, dbytes) = $group_impl::max_compressed_bytes(expected.len());
+                    let mut tags = vec![0u8; tbytes];
+                    let mut data = vec![0u8; dbytes];
+                    let data_len = coder.encode_deltas(initial, &expected, &mut tags, &mut data);
+                    data.truncate(data_len);
+                    let mut deltas = vec![0; expected.len()];
+                    coder.decode(&tags, &data, &mut deltas);
+
+                    let mut actual = vec![std::mem::MaybeUninit::uninit(); expected.len()];
+                    assert_eq!(data_len, coder.decode_uninit(&tags, &data, &mut actual));
+                    assert!(deltas.iter().zip(actual.iter()).all(|(d, a)| *d == unsafe { a.assume_init() }));
+                    assert_eq!(data_len, coder.decode_deltas_uninit(initial, &tags, &data, &mut actual));
+                    assert!(expected.iter().zip(actual.iter()).all(|(e, a)| *e == unsafe { a.assume_init() }));
+
+                    let mut actual = vec![7];
+                    assert_eq!(data_len, coder.decode_to_vec(&tags, &data, &mut actual));
+                    assert_eq!(7, actual[0]);
+                    assert_eq!(deltas, actual[1..]);
+                    let mut actual = vec![];
+                    assert_eq!(data_len, coder.decode_deltas_to_vec(initial, &tags, &data, &mut actual));
+                    assert_eq!(expected, actual);
+                }
+            }
+
+            #[test]
+            fn encode_iter() {
+                let coder = $group_impl::new();
+                for max_bytes in $coding_descriptor::TAG_LEN {
+                    let initial = 1;
+                    for len in [0, 1, 31, 32, 33, 1001] {
+                        let values = generate_cumulative_array(len, max_bytes, initial);
+                        let (tbytes, dbytes) = $group_impl::max_compressed_bytes(len);
+
+                        let mut padded = values.clone();
+                        padded.resize(tbytes * 4, 0);
+                        let mut expected_tags = vec![0u8; tbytes];
+                        let mut expected_data = vec![0u8; dbytes];
+                        let expected_len = coder.encode(&padded, &mut expected_tags, &mut expected_data);
+                        let mut tags = vec![0u8; tbytes];
+                        let mut data = vec![0u8; dbytes];
+                        assert_eq!((len, expected_len), coder.encode_iter(values.iter().copied(), &mut tags, &mut data));
+                        assert_eq!(expected_tags, tags, "max_bytes={} len={}", max_bytes, len);
+                        assert_eq!(expected_data[..expected_len], data[..expected_len], "max_bytes={} len={}", max_bytes, len);
+
+                        let mut padded = values.clone();
+                        padded.resize(tbytes * 4, values.last().copied().unwrap_or(initial));
+                        let expected_len = coder.encode_deltas(initial, &padded, &mut expected_tags, &mut expected_data);
+                        assert_eq!((len, expected_len), coder.encode_deltas_iter(initial, values.iter().copied(), &mut tags, &mut data));
+                        assert_eq!(expected_tags, tags, "max_bytes={} len={}", max_bytes, len);
+                        assert_eq!(expected_data[..expected_len], data[..expected_len], "max_bytes={} len={}", max_bytes, len);
+                    }
+                }
+
+                // Iteration stops when the tag stream is full.
+                let mut tags = vec![0u8; 9];
+                let mut data = vec![0u8; 9 * 4 * std::mem::size_of::<<$group_impl as Coder>::Elem>()];
+                let mut values = 0..100;
+                let (consumed, _) = coder.encode_iter((&mut values).map(|v| v as _), &mut tags, &mut data);
+                assert_eq!(36, consumed);
+                assert_eq!(Some(36), values.next());
+            }
+
+            #[test]
+            fn encode_into_vec() {
+                let coder = $group_impl::new();
+                for max_bytes in $coding_descriptor::TAG_LEN {
+                    let initial = 1;
+                    for len in [0, 4, 32, 36, 1000] {
+                        let values = generate_cumulative_array(len, max_bytes, initial);
+                        let (tbytes, dbytes) = $group_impl::max_compressed_bytes(len);
+                        let mut expected_tags = vec![0u8; tbytes];
+                        let mut expected_data = vec![0u8; dbytes];
+
+                        let expected_len = coder.encode(&values, &mut expected_tags, &mut expected_data);
+                        let mut tags = vec![0xffu8];
+                        let mut data = vec![0xffu8];
+                        assert_eq!(expected_len, coder.encode_into_vec(&values, &mut tags, &mut data));
+                        assert_eq!(expected_tags, tags[1..], "max_bytes={} len={}", max_bytes, len);
+                        assert_eq!(expected_data[..expected_len], data[1..], "max_bytes={} len={}", max_bytes, len);
+
+                        let expected_len = coder.encode_deltas(initial, &values, &mut expected_tags, &mut expected_data);
+                        let mut tags = vec![];
+                        let mut data = vec![];
+                        assert_eq!(expected_len, coder.encode_deltas_into_vec(initial, &values, &mut tags, &mut data));
+                        assert_eq!(expected_tags, tags, "max_bytes={} len={}", max_bytes, len);
+                        assert_eq!(expected_data[..expected_len], data, "max_bytes={} len={}", max_bytes, len);
+                    }
+                }
+            }
+
+            #[test]
+            fn encode_bounded() {
+                let coder = $group_impl::new();
+                for max_bytes in $coding_descriptor::TAG_LEN {
+                    let initial = 1;
+                    let values = generate_cumulative_array(1024, max_bytes, initial);
+                    let (tbytes, dbytes) = $group_impl::max_compressed_bytes(values.len());
+                    let mut expected_tags = vec![0u8; tbytes];
+                    let mut expected_data = vec![0u8; dbytes];
+                    let plain_len = coder.encode(&values, &mut expected_tags, &mut expected_data);
+                    let plain = (expected_tags.clone(), expected_data[..plain_len].to_vec());
+                    let delta_len = coder.encode_deltas(initial, &values, &mut expected_tags, &mut expected_data);
+                    let delta = (expected_tags, expected_data[..delta_len].to_vec());
+
+                    for capacity in [0, 1, 7, 100, 101, 1000, 4096, 10_000] {
+                        for (delta_coded, (expected_tags, expected_data)) in [(false, &plain), (true, &delta)] {
+                            let groups = (0..=tbytes).rev().find(|g| coder.data_len(&expected_tags[..*g]) <= capacity).unwrap();
+                            let expected_len = coder.data_len(&expected_tags[..groups]);
+
+                            let mut tags = vec![0u8; tbytes];
+                            let mut data = vec![0xa5u8; capacity + 64];
+                            let (consumed, written) = if delta_coded {
+                                coder.encode_deltas_bounded(initial, &values, &mut tags, &mut data[..capacity])
+                            } else {
+                                coder.encode_bounded(&values, &mut tags, &mut data[..capacity])
+                            };
+                            assert_eq!((groups * 4, expected_len), (consumed, written), "max_bytes={} capacity={} delta={}", max_bytes, capacity, delta_coded);
+                            assert_eq!(expected_tags[..groups], tags[..groups]);
+                            assert_eq!(expected_data[..written], data[..written]);
+                            assert!(data[capacity..].iter().all(|b| *b == 0xa5));
+                        }
+                    }
+
+                    // A trailing partial group is consumed when it fits.
+                    let mut tags = vec![0u8; 1];
+                    let mut data = vec![0u8; 64];
+                    assert_eq!(3, coder.encode_bounded(&values[..3], &mut tags, &mut data).0);
+                    assert_eq!(3, coder.encode_deltas_bounded(initial, &values[..3], &mut tags, &mut data).0);
+                }
+            }
+
+            #[test]
+            fn compressed_len() {
+                let coder = $group_impl::new();
+                for max_bytes in $coding_descriptor::TAG_LEN {
+                    let initial = 1;
+                    let values = generate_cumulative_array(4096, max_bytes, initial);
+                    let (tbytes, dbytes) = $group_impl::max_compressed_bytes(values.len());
+                    let mut tags = vec![0u8; tbytes];
+                    let mut data = vec![0u8; dbytes];
+                    assert_eq!(coder.encode(&values, &mut tags, &mut data), coder.compressed_len(&values));
+                    assert_eq!(
+                        coder.encode_deltas(initial, &values, &mut tags, &mut data),
+                        coder.compressed_len_deltas(initial, &values)
+                    );
+                }
+            }
+
             // TODO: test boundary conditions on encode and decode unrolling.
         }
     }
@@ -0,0 +1,321 @@
+//! Growable vector of integers stored in encoded blocks.
+
+use crate::Coder;
+use num_traits::Zero;
+use std::sync::{Mutex, TryLockError};
+
+/// Number of groups in each encoded block.
+const BLOCK_GROUPS: usize = 32;
+/// Number of values in each encoded block.
+const BLOCK_LEN: usize = BLOCK_GROUPS * 4;
+
+/// A growable sequence of integers that stores values in encoded blocks of 128 values.
+///
+/// Values are appended to an uncompressed tail which is encoded with `Coder::encode()` once it
+/// fills a block. `get()` decodes the block containing the requested value and caches it so that
+/// nearby lookups do not decode it again. The cache is guarded by a `Mutex`, so `CompressedVec` is
+/// `Sync`; a `get()` that finds the cache in use by another thread decodes only the requested value
+/// with `Coder::decode_value()` instead of waiting.
+///
+/// ```
+/// use streamvbyte64::{Coder1234, CompressedVec};
+///
+/// let mut v = CompressedVec::<Coder1234>::default();
+/// v.extend((0..1000u32).map(|i| i * i));
+/// v.push(7);
+/// assert_eq!(1001, v.len());
+/// assert_eq!(Some(250_000), v.get(500));
+/// assert_eq!(Some(7), v.get(1000));
+/// assert_eq!(None, v.get(1001));
+/// assert_eq!(Some(998_001), v.iter().nth(999));
+/// ```
+pub struct CompressedVec<C: Coder> {
+    coder: C,
+    tags: Vec<u8>,
+    data: Vec<u8>,
+    /// Data offset of the first group of each block.
+    offsets: Vec<usize>,
+    /// Values that have not yet filled a block.
+    tail: Vec<C::Elem>,
+    /// Most recently decoded block and its index.
+    cache: Mutex<(Option<usize>, Vec<C::Elem>)>,
+}
+
+impl<C: Coder> CompressedVec<C> {
+    /// Create an empty vector that encodes values with `coder`.
+    pub fn new(coder: C) -> Self {
+        Self {
+            coder,
+            tags: vec![],
+            data: vec![],
+            offsets: vec![],
+            tail: Vec::with_capacity(BLOCK_LEN),
+            cache: Mutex::new((None, vec![])),
+        }
+    }
+
+    /// Returns the number of values in the vector.
+    pub fn len(&self) -> usize {
+        self.offsets.len() * BLOCK_LEN + self.tail.len()
+    }
+
+    /// Returns true if the vector contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of bytes allocated on the heap by this vector, including the cache.
+    pub fn heap_size(&self) -> usize {
+        self.tags.capacity()
+            + self.data.capacity()
+            + self.offsets.capacity() * std::mem::size_of::<usize>()
+            + (self.tail.capacity() + self.cache_capacity()) * std::mem::size_of::<C::Elem>()
+    }
+
+    /// Returns the capacity of the cached block.
+    fn cache_capacity(&self) -> usize {
+        match self.cache.lock() {
+            Ok(cache) => cache.1.capacity(),
+            Err(e) => e.into_inner().1.capacity(),
+        }
+    }
+
+    /// Append `value` to the end of the vector.
+    pub fn push(&mut self, value: C::Elem) {
+        self.tail.push(value);
+        if self.tail.len() == BLOCK_LEN {
+            self.flush_tail();
+        }
+    }
+
+    /// Encode the tail as a new block.
+    fn flush_tail(&mut self) {
+        self.offsets.push(self.data.len());
+        self.coder
+            .encode_into_vec(&self.tail, &mut self.tags, &mut self.data);
+        self.tail.clear();
+    }
+
+    /// Decode `block` into the start of `values`.
+    fn decode_block(&self, block: usize, values: &mut [C::Elem]) {
+        let tags = &self.tags[(block * BLOCK_GROUPS)..((block + 1) * BLOCK_GROUPS)];
+        self.coder.decode(
+            tags,
+            &self.data[self.offsets[block]..],
+            &mut values[..BLOCK_LEN],
+        );
+    }
+
+    /// Returns the value at `index`, or `None` if `index >= len()`.
+    pub fn get(&self, index: usize) -> Option<C::Elem> {
+        let block = index / BLOCK_LEN;
+        if block >= self.offsets.len() {
+            return self
+                .tail
+                .get(index - self.offsets.len() * BLOCK_LEN)
+                .copied();
+        }
+        let mut cache = match self.cache.try_lock() {
+            Ok(cache) => cache,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return Some(self.decode_value(index)),
+        };
+        if cache.0 != Some(block) {
+            cache.1.resize(BLOCK_LEN, C::Elem::zero());
+            self.decode_block(block, &mut cache.1);
+            cache.0 = Some(block);
+        }
+        Some(cache.1[index % BLOCK_LEN])
+    }
+
+    /// Decode only the encoded value at `index`.
+    fn decode_value(&self, index: usize) -> C::Elem {
+        let block = index / BLOCK_LEN;
+        let group = index / 4;
+        let offset = self.offsets[block]
+            + self
+                .coder
+                .data_len(&self.tags[(block * BLOCK_GROUPS)..group]);
+        self.coder
+            .decode_value(self.tags[group], &self.data[offset..], index % 4)
+    }
+
+    /// Iterate over all values in the vector.
+    pub fn iter(&self) -> Iter<'_, C> {
+        Iter {
+            vec: self,
+            pos: 0,
+            buf: vec![],
+        }
+    }
+}
+
+impl<C: Coder> Clone for CompressedVec<C> {
+    /// Clones the encoded values; the clone starts with an empty cache.
+    fn clone(&self) -> Self {
+        Self {
+            coder: self.coder,
+            tags: self.tags.clone(),
+            data: self.data.clone(),
+            offsets: self.offsets.clone(),
+            tail: self.tail.clone(),
+            cache: Mutex::new((None, vec![])),
+        }
+    }
+}
+
+impl<C: Coder> Default for CompressedVec<C> {
+    fn default() -> Self {
+        Self::new(C::new())
+    }
+}
+
+impl<C: Coder> Extend<C::Elem> for CompressedVec<C> {
+    fn extend<I: IntoIterator<Item = C::Elem>>(&mut self, iter: I) {
+        for v in iter {
+            self.push(v);
+        }
+    }
+}
+
+impl<C: Coder> FromIterator<C::Elem> for CompressedVec<C> {
+    fn from_iter<I: IntoIterator<Item = C::Elem>>(iter: I) -> Self {
+        let mut v = Self::default();
+        v.extend(iter);
+        v
+    }
+}
+
+impl<C: Coder> std::fmt::Debug for CompressedVec<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, C: Coder> IntoIterator for &'a CompressedVec<C> {
+    type Item = C::Elem;
+    type IntoIter = Iter<'a, C>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the values of a `CompressedVec`, decoding one block at a time.
+pub struct Iter<'a, C: Coder> {
+    vec: &'a CompressedVec<C>,
+    pos: usize,
+    buf: Vec<C::Elem>,
+}
+
+impl<C: Coder> Iterator for Iter<'_, C> {
+    type Item = C::Elem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block = self.pos / BLOCK_LEN;
+        let offset = self.pos % BLOCK_LEN;
+        let v = if block < self.vec.offsets.len() {
+            if offset == 0 {
+                self.buf.resize(BLOCK_LEN, C::Elem::zero());
+                self.vec.decode_block(block, &mut self.buf);
+            }
+            self.buf[offset]
+        } else {
+            *self.vec.tail.get(offset)?
+        };
+        self.pos += 1;
+        Some(v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.vec.len() - self.pos;
+        (len, Some(len))
+    }
+}
+
+impl<C: Coder> ExactSizeIterator for Iter<'_, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::generate_array;
+    use crate::{Coder0124, Coder1234, Coder1248};
+    use rand::prelude::*;
+
+    fn check<C: Coder>(coder: C) {
+        for len in [0, 1, 127, 128, 129, 1000] {
+            let expected = generate_array::<C::Elem>(len, std::mem::size_of::<C::Elem>());
+            let mut v = CompressedVec::new(coder);
+            v.extend(expected.iter().copied());
+            assert_eq!(expected.len(), v.len());
+            assert_eq!(expected.is_empty(), v.is_empty());
+            assert_eq!(expected, v.iter().collect::<Vec<_>>());
+            assert_eq!(expected.len(), v.iter().len());
+
+            // Visit indices in random order to exercise the block cache.
+            let mut indices = (0..(len + 2)).collect::<Vec<_>>();
+            indices.shuffle(&mut StdRng::from_seed([0x77u8; 32]));
+            for i in indices {
+                assert_eq!(expected.get(i).copied(), v.get(i), "index {}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn coder0124() {
+        check(Coder0124::new());
+    }
+
+    #[test]
+    fn coder1234() {
+        check(Coder1234::new());
+    }
+
+    #[test]
+    fn coder1248() {
+        check(Coder1248::new());
+    }
+
+    #[test]
+    fn is_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<CompressedVec<Coder1234>>();
+    }
+
+    #[test]
+    fn shared_get() {
+        let expected = generate_array::<u32>(1000, 4);
+        let v = expected
+            .iter()
+            .copied()
+            .collect::<CompressedVec<Coder1234>>();
+        // Lookups made while the cache is held decode the requested value directly.
+        let guard = v.cache.lock().unwrap();
+        for (i, e) in expected.iter().enumerate() {
+            assert_eq!(Some(*e), v.get(i));
+        }
+        drop(guard);
+
+        std::thread::scope(|s| {
+            for t in 0..4 {
+                let (v, expected) = (&v, &expected);
+                s.spawn(move || {
+                    for i in (t..expected.len()).step_by(3) {
+                        assert_eq!(Some(expected[i]), v.get(i));
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn heap_size() {
+        let small = (0..1000u32).collect::<CompressedVec<Coder1234>>();
+        let large = (0..1000u32)
+            .map(|v| v << 20)
+            .collect::<CompressedVec<Coder1234>>();
+        assert!(small.heap_size() > 0);
+        assert!(small.heap_size() < large.heap_size());
+        assert!(large.heap_size() < 1000 * 4 * 2);
+    }
+}
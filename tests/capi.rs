@@ -0,0 +1,72 @@
+//! Builds `tests/capi/capi_test.c` against the static library and header and runs it.
+//!
+//! `cargo test` only builds the `rlib`. If `STREAMVBYTE64_STATICLIB` names a static library built
+//! with
+//!     cargo rustc --lib --features capi --crate-type staticlib
+//! it is used, otherwise the test builds one in its own target directory.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Build the static library in a target directory under `tmp_dir` and return its path.
+fn build_staticlib(manifest_dir: &Path, tmp_dir: &Path) -> PathBuf {
+    let target_dir = tmp_dir.join("capi-staticlib");
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let status = Command::new(cargo)
+        .arg("rustc")
+        .arg("--manifest-path")
+        .arg(manifest_dir.join("Cargo.toml"))
+        .args(["--lib", "--features", "capi", "--crate-type", "staticlib"])
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "building the static library failed");
+    let name = if cfg!(target_env = "msvc") {
+        "streamvbyte64.lib"
+    } else {
+        "libstreamvbyte64.a"
+    };
+    target_dir.join("debug").join(name)
+}
+
+#[test]
+fn c_program() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let tmp_dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let staticlib = std::env::var_os("STREAMVBYTE64_STATICLIB")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| build_staticlib(manifest_dir, tmp_dir));
+    assert!(
+        Path::new(&staticlib).exists(),
+        "static library {:?} does not exist",
+        staticlib
+    );
+    let out = tmp_dir.join("capi_test");
+    let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+
+    let mut build = Command::new(cc);
+    build
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/capi/capi_test.c"))
+        .arg(&staticlib)
+        .arg("-o")
+        .arg(&out);
+    if !cfg!(target_os = "windows") {
+        build.args(["-lpthread", "-ldl", "-lm"]);
+    }
+    let status = build.status().expect("failed to run C compiler");
+    assert!(status.success(), "C compilation failed");
+
+    let output = Command::new(&out).output().unwrap();
+    assert!(
+        output.status.success(),
+        "C test failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(b"ok\n", output.stdout.as_slice());
+}
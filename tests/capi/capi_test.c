@@ -0,0 +1,92 @@
+/* Exercises the C ABI declared in include/streamvbyte64.h. Run via tests/capi.rs. */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "streamvbyte64.h"
+
+#define LEN 1024
+
+#define CHECK(cond)                                                        \
+  do {                                                                     \
+    if (!(cond)) {                                                         \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+      return 1;                                                            \
+    }                                                                      \
+  } while (0)
+
+/* Round trip plain and delta coded values for one coder. */
+#define TEST_CODER(name, elem_t)                                                         \
+  static int test_##name(void) {                                                         \
+    elem_t values[LEN];                                                                  \
+    elem_t decoded[LEN];                                                                 \
+    elem_t sum = 0;                                                                      \
+    size_t tags_len, data_len, written, read, len;                                       \
+    elem_t skip_sum;                                                                     \
+    uint8_t *tags, *data;                                                                \
+    for (size_t i = 0; i < LEN; i++) {                                                   \
+      values[i] = (elem_t)((i * 2654435761u) >> (i % 24));                              \
+    }                                                                                    \
+    CHECK(streamvbyte64_##name##_max_compressed_bytes(LEN, &tags_len, &data_len) ==      \
+          STREAMVBYTE64_STATUS_OK);                                                      \
+    tags = malloc(tags_len);                                                             \
+    data = malloc(data_len);                                                             \
+                                                                                         \
+    CHECK(streamvbyte64_##name##_encode(values, LEN, tags, tags_len, data, data_len,     \
+                                        &written) == STREAMVBYTE64_STATUS_OK);           \
+    CHECK(streamvbyte64_##name##_data_len(tags, tags_len, &len) ==                       \
+          STREAMVBYTE64_STATUS_OK);                                                      \
+    CHECK(len == written);                                                               \
+    CHECK(streamvbyte64_##name##_decode(tags, tags_len, data, written, decoded, LEN,     \
+                                        &read) == STREAMVBYTE64_STATUS_OK);              \
+    CHECK(read == written);                                                              \
+    CHECK(memcmp(values, decoded, sizeof(values)) == 0);                                 \
+                                                                                         \
+    for (size_t i = 0; i < LEN; i++) {                                                   \
+      sum += values[i] & 0xfff;                                                          \
+      values[i] = sum;                                                                   \
+    }                                                                                    \
+    CHECK(streamvbyte64_##name##_encode_deltas(7, values, LEN, tags, tags_len, data,     \
+                                               data_len, &written) ==                    \
+          STREAMVBYTE64_STATUS_OK);                                                      \
+    CHECK(streamvbyte64_##name##_skip_deltas(tags, tags_len, data, written, &read,       \
+                                             &skip_sum) == STREAMVBYTE64_STATUS_OK);     \
+    CHECK(read == written);                                                              \
+    CHECK(skip_sum == (elem_t)(values[LEN - 1] - 7));                                    \
+    CHECK(streamvbyte64_##name##_decode_deltas(7, tags, tags_len, data, written,         \
+                                               decoded, LEN, &read) ==                   \
+          STREAMVBYTE64_STATUS_OK);                                                      \
+    CHECK(read == written);                                                              \
+    CHECK(memcmp(values, decoded, sizeof(values)) == 0);                                 \
+                                                                                         \
+    /* Errors are reported as status codes. */                                           \
+    CHECK(streamvbyte64_##name##_encode(values, LEN - 1, tags, tags_len, data, data_len, \
+                                        &written) == STREAMVBYTE64_STATUS_INVALID_LENGTH); \
+    CHECK(streamvbyte64_##name##_encode(values, LEN, tags, tags_len - 1, data, data_len, \
+                                        &written) ==                                     \
+          STREAMVBYTE64_STATUS_BUFFER_TOO_SMALL);                                        \
+    CHECK(streamvbyte64_##name##_decode(tags, tags_len, data, read - 1, decoded, LEN,    \
+                                        &read) == STREAMVBYTE64_STATUS_TRUNCATED);       \
+    CHECK(streamvbyte64_##name##_decode(NULL, tags_len, data, data_len, decoded, LEN,    \
+                                        &read) == STREAMVBYTE64_STATUS_NULL_POINTER);    \
+    CHECK(streamvbyte64_##name##_max_compressed_bytes(SIZE_MAX, &tags_len, &data_len) == \
+          STREAMVBYTE64_STATUS_OVERFLOW);                                                \
+    CHECK(streamvbyte64_##name##_skip_deltas(tags, SIZE_MAX / 2, data, data_len, &read,  \
+                                             &skip_sum) == STREAMVBYTE64_STATUS_OVERFLOW); \
+                                                                                         \
+    free(tags);                                                                          \
+    free(data);                                                                          \
+    return 0;                                                                            \
+  }
+
+TEST_CODER(coder0124, uint32_t)
+TEST_CODER(coder1234, uint32_t)
+TEST_CODER(coder1248, uint64_t)
+
+int main(void) {
+  if (test_coder0124() || test_coder1234() || test_coder1248()) {
+    return 1;
+  }
+  printf("ok\n");
+  return 0;
+}
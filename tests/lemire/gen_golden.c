@@ -0,0 +1,35 @@
+/*
+ * Prints the golden vectors used by the tests in src/lemire.rs using the encoders from
+ * https://github.com/lemire/streamvbyte, so that they can be checked against the C library.
+ *
+ * Build the C library first, then link against it:
+ *
+ *     cc -std=c99 -I streamvbyte/include tests/lemire/gen_golden.c -L streamvbyte/build \
+ *         -lstreamvbyte -o gen_golden && ./gen_golden
+ */
+#include <stdint.h>
+#include <stdio.h>
+
+#include "streamvbyte.h"
+#include "streamvbyte_delta.h"
+
+static void print(const char *name, const uint8_t *out, size_t len) {
+  printf("%s [%zu]:", name, len);
+  for (size_t i = 0; i < len; i++) {
+    printf(" 0x%02x,", out[i]);
+  }
+  printf("\n");
+}
+
+int main(void) {
+  uint8_t out[64];
+  const uint32_t values[10] = {0, 1, 255, 256, 65535, 65536, 16777215, 16777216, 0xffffffff, 300};
+  print("GOLDEN_1234", out, streamvbyte_encode(values, 10, out));
+  print("GOLDEN_0124", out, streamvbyte_encode_0124(values, 10, out));
+
+  const uint32_t delta_values[5] = {10, 11, 266, 266, 70000};
+  print("GOLDEN_DELTA", out, streamvbyte_delta_encode(delta_values, 5, out, 10));
+  const uint32_t wrap_values[2] = {5, 3};
+  print("GOLDEN_DELTA_WRAP", out, streamvbyte_delta_encode(wrap_values, 2, out, 0));
+  return 0;
+}